@@ -8,6 +8,7 @@ pub struct Version {
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
     println!("cargo:rerun-if-env-changed=CARGO_CFG_TARGET_ARCH");
+    println!("cargo:rustc-check-cfg=cfg(extern_cstr)");
 
     if std::env::var("CARGO_CFG_TARGET_ARCH").unwrap() == "loongarch64" {
         return;
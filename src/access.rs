@@ -0,0 +1,276 @@
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use linux_syscalls::{bitflags, syscall, Errno, Sysno};
+
+use crate::{
+    fstatat_cstr, run_with_cstr, sys, CStr, ModePermission, Path, RawFd, StatAtFlags,
+    CURRENT_DIRECTORY,
+};
+
+static HAS_FACCESSAT2: AtomicU8 = AtomicU8::new(2);
+
+bitflags! {
+    /// Flags for `faccessat()`.
+    #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub enum AccessAtFlags: u32 {
+        /// Perform access checks using the effective user and group IDs.
+        /// By default, faccessat() uses the real IDs (like access()).
+        EACCESS = linux_raw_sys::general::AT_EACCESS,
+        /// If pathname is a symbolic link, do not dereference it: instead
+        /// return information about the link itself.
+        SYMLINK_NOFOLLOW = linux_raw_sys::general::AT_SYMLINK_NOFOLLOW,
+        /// If pathname is an empty string, operate on the file referred to
+        /// by dirfd (which may have been obtained using the open(2) O_PATH
+        /// flag).
+        EMPTY_PATH = linux_raw_sys::general::AT_EMPTY_PATH,
+    }
+}
+
+/// Emulate `faccessat2` on top of `faccessat`, which has no flags.
+unsafe fn faccessat_fallback(
+    dirfd: RawFd,
+    path: &CStr,
+    mode: u32,
+    flags: AccessAtFlags,
+) -> Result<(), Errno> {
+    if flags.contains(AccessAtFlags::EACCESS)
+        && (sys::getuid() != sys::geteuid() || sys::getgid() != sys::getegid())
+    {
+        return Err(Errno::ENOSYS);
+    }
+
+    let mut buf = [0u8; 32];
+    let path = if flags.contains(AccessAtFlags::EMPTY_PATH) && path.to_bytes().is_empty() {
+        if dirfd == CURRENT_DIRECTORY {
+            CStr::from_bytes_with_nul_unchecked(b".\0")
+        } else if dirfd < 0 {
+            return Err(Errno::EBADF);
+        } else {
+            sys::proc_self_fd(dirfd, &mut buf)
+        }
+    } else {
+        if flags.contains(AccessAtFlags::SYMLINK_NOFOLLOW)
+            && fstatat_cstr(dirfd, path, StatAtFlags::SYMLINK_NOFOLLOW)?.is_link()
+        {
+            // Symbolic links are always `rwxrwxrwx`.
+            return Ok(());
+        }
+        path
+    };
+
+    crate::retry(|| syscall!(Sysno::faccessat, dirfd, path.as_ptr(), mode)).map(|_| ())
+}
+
+unsafe fn faccessat_raw(
+    dirfd: RawFd,
+    path: &CStr,
+    mode: ModePermission,
+    flags: AccessAtFlags,
+) -> Result<(), Errno> {
+    let mode = mode.bits() as u32;
+
+    if flags == AccessAtFlags::empty() {
+        return crate::retry(|| syscall!(Sysno::faccessat, dirfd, path.as_ptr(), mode)).map(|_| ());
+    }
+
+    match HAS_FACCESSAT2.load(Ordering::Relaxed) {
+        0 => faccessat_fallback(dirfd, path, mode, flags),
+        1 => crate::retry(|| syscall!(Sysno::faccessat2, dirfd, path.as_ptr(), mode, flags.bits()))
+            .map(|_| ()),
+        _ => match crate::retry(|| {
+            syscall!(Sysno::faccessat2, dirfd, path.as_ptr(), mode, flags.bits())
+        }) {
+            Err(Errno::ENOSYS) => {
+                HAS_FACCESSAT2.store(0, Ordering::Relaxed);
+                faccessat_fallback(dirfd, path, mode, flags)
+            }
+            other => {
+                HAS_FACCESSAT2.store(1, Ordering::Relaxed);
+                other.map(|_| ())
+            }
+        },
+    }
+}
+
+/// Check whether the calling process can access `path` relative to `dirfd`
+/// with all the permissions in `mode`. An empty `mode` checks the existence
+/// of the file.
+///
+/// Returns `Ok(false)` if access is denied ([Errno::EACCES], [Errno::EPERM],
+/// [Errno::EROFS] or [Errno::ETXTBSY]).
+///
+/// If not empty, `flags` are passed to `faccessat2` (Linux 5.8). On older
+/// kernels it is emulated on top of `faccessat`:
+/// - [AccessAtFlags::EACCESS] fails with [Errno::ENOSYS] unless the real
+///   and effective user and group IDs are the same;
+/// - [AccessAtFlags::SYMLINK_NOFOLLOW] is checked with a separate
+///   [crate::fstatat] and is racy;
+/// - [AccessAtFlags::EMPTY_PATH] goes through `/proc/self/fd`.
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+#[inline]
+pub unsafe fn faccessat<P: AsRef<Path>>(
+    dirfd: RawFd,
+    path: P,
+    mode: ModePermission,
+    flags: AccessAtFlags,
+) -> Result<bool, Errno> {
+    run_with_cstr(path, |path| faccessat_cstr(dirfd, path, mode, flags))
+}
+
+/// Check whether the calling process can access `path` relative to `dirfd`
+/// with all the permissions in `mode`. Accept `path` as a [CStr].
+///
+/// See [crate::faccessat].
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+pub unsafe fn faccessat_cstr(
+    dirfd: RawFd,
+    path: &CStr,
+    mode: ModePermission,
+    flags: AccessAtFlags,
+) -> Result<bool, Errno> {
+    match faccessat_raw(dirfd, path, mode, flags) {
+        Ok(()) => Ok(true),
+        Err(Errno::EACCES | Errno::EPERM | Errno::EROFS | Errno::ETXTBSY) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Returns the permissions the calling process has on `path` relative to
+/// `dirfd`, each checked on its own with [crate::faccessat].
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+#[inline]
+pub unsafe fn faccessat_permissions<P: AsRef<Path>>(
+    dirfd: RawFd,
+    path: P,
+    flags: AccessAtFlags,
+) -> Result<ModePermission, Errno> {
+    run_with_cstr(path, |path| faccessat_permissions_cstr(dirfd, path, flags))
+}
+
+/// Returns the permissions the calling process has on `path` relative to
+/// `dirfd`, each checked on its own with [crate::faccessat_cstr].
+/// Accept `path` as a [CStr].
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+pub unsafe fn faccessat_permissions_cstr(
+    dirfd: RawFd,
+    path: &CStr,
+    flags: AccessAtFlags,
+) -> Result<ModePermission, Errno> {
+    let mut res = ModePermission::empty();
+    for perm in [
+        ModePermission::READ,
+        ModePermission::WRITE,
+        ModePermission::EXEC,
+    ] {
+        if faccessat_cstr(dirfd, path, perm, flags)? {
+            res.insert(perm);
+        }
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{retry, tests::dev_null};
+
+    #[test]
+    fn dev_null_access() {
+        linux_syscalls::init();
+
+        let rw = ModePermission::READ | ModePermission::WRITE;
+        for flags in [
+            AccessAtFlags::empty(),
+            AccessAtFlags::EACCESS,
+            AccessAtFlags::SYMLINK_NOFOLLOW,
+        ] {
+            assert_eq!(
+                retry(|| unsafe { faccessat(CURRENT_DIRECTORY, dev_null(), rw, flags) }),
+                Ok(true)
+            );
+            assert_eq!(
+                retry(|| unsafe {
+                    run_with_cstr(dev_null(), |path| {
+                        faccessat_fallback(CURRENT_DIRECTORY, path, rw.bits() as u32, flags)
+                    })
+                }),
+                Ok(())
+            );
+        }
+
+        assert_eq!(
+            retry(|| unsafe {
+                faccessat(
+                    CURRENT_DIRECTORY,
+                    dev_null(),
+                    ModePermission::empty(),
+                    AccessAtFlags::empty(),
+                )
+            }),
+            Ok(true)
+        );
+        let perms = retry(|| unsafe {
+            faccessat_permissions(CURRENT_DIRECTORY, dev_null(), AccessAtFlags::EACCESS)
+        })
+        .unwrap();
+        assert!(perms.contains(rw));
+
+        let fd = retry(|| unsafe {
+            run_with_cstr(dev_null(), |path| {
+                sys::openat(
+                    CURRENT_DIRECTORY,
+                    path,
+                    linux_raw_sys::general::O_PATH | linux_raw_sys::general::O_CLOEXEC,
+                    0,
+                )
+            })
+        })
+        .unwrap();
+        let empty = unsafe { CStr::from_bytes_with_nul_unchecked(b"\0") };
+        assert_eq!(
+            retry(|| unsafe {
+                faccessat_cstr(fd.as_raw_fd(), empty, rw, AccessAtFlags::EMPTY_PATH)
+            }),
+            Ok(true)
+        );
+        assert_eq!(
+            retry(|| unsafe {
+                faccessat_fallback(
+                    fd.as_raw_fd(),
+                    empty,
+                    rw.bits() as u32,
+                    AccessAtFlags::EMPTY_PATH,
+                )
+            }),
+            Ok(())
+        );
+
+        assert_eq!(
+            retry(|| unsafe {
+                faccessat(
+                    CURRENT_DIRECTORY,
+                    crate::empty_path(),
+                    rw,
+                    AccessAtFlags::empty(),
+                )
+            }),
+            Err(Errno::ENOENT)
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use linux_syscalls::Errno;
+
+use crate::Dev;
+
+/// Block device metadata read from `/sys/dev/block/<major>:<minor>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BlockDevice {
+    dev: Dev,
+    sys_path: PathBuf,
+    name: String,
+    disk: String,
+    partition: Option<u32>,
+    size: u64,
+    logical_block_size: u32,
+    rotational: bool,
+    read_only: bool,
+}
+
+#[inline]
+fn io_errno(err: std::io::Error) -> Errno {
+    err.raw_os_error().map_or(Errno::EIO, Errno::new)
+}
+
+fn read_attr<T: FromStr>(path: &Path) -> Result<T, Errno> {
+    fs::read_to_string(path)
+        .map_err(io_errno)?
+        .trim()
+        .parse()
+        .map_err(|_| Errno::EINVAL)
+}
+
+fn read_flag(path: &Path) -> Result<bool, Errno> {
+    read_attr::<u8>(path).map(|v| v != 0)
+}
+
+fn file_name(path: &Path) -> Result<String, Errno> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map(str::to_owned)
+        .ok_or(Errno::EINVAL)
+}
+
+impl BlockDevice {
+    /// Read the metadata of the block device `dev` from sysfs.
+    ///
+    /// Returns [Errno::ENOENT] if `dev` is not a block device known to the
+    /// running kernel.
+    pub fn from_dev(dev: Dev) -> Result<Self, Errno> {
+        let sys_path = fs::canonicalize(format!("/sys/dev/block/{}:{}", dev.major(), dev.minor()))
+            .map_err(io_errno)?;
+
+        let partition_path = sys_path.join("partition");
+        let partition = if partition_path.exists() {
+            Some(read_attr(&partition_path)?)
+        } else {
+            None
+        };
+        let disk_path = if partition.is_some() {
+            sys_path.parent().ok_or(Errno::EINVAL)?
+        } else {
+            &sys_path
+        };
+        let queue = disk_path.join("queue");
+
+        Ok(Self {
+            dev,
+            name: file_name(&sys_path)?,
+            disk: file_name(disk_path)?,
+            partition,
+            size: read_attr(&sys_path.join("size"))?,
+            logical_block_size: read_attr(&queue.join("logical_block_size"))?,
+            rotational: read_flag(&queue.join("rotational"))?,
+            read_only: read_flag(&sys_path.join("ro"))?,
+            sys_path,
+        })
+    }
+
+    /// Returns the device id.
+    #[inline]
+    pub const fn dev(&self) -> Dev {
+        self.dev
+    }
+
+    /// Returns the canonical sysfs directory of the device.
+    #[inline]
+    pub fn sys_path(&self) -> &Path {
+        &self.sys_path
+    }
+
+    /// Returns the kernel name of the device (e.g. `sda1`, `nvme0n1p2`).
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the kernel name of the whole disk containing the device, same
+    /// as [BlockDevice::name] if the device is not a partition.
+    #[inline]
+    pub fn disk(&self) -> &str {
+        &self.disk
+    }
+
+    /// Returns the partition number or `None` if the device is a whole disk.
+    #[inline]
+    pub const fn partition(&self) -> Option<u32> {
+        self.partition
+    }
+
+    /// Returns true if the device is a partition.
+    #[inline]
+    pub const fn is_partition(&self) -> bool {
+        self.partition.is_some()
+    }
+
+    /// Returns the size of the device in 512-byte sectors.
+    #[inline]
+    pub const fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the logical block size of the disk in bytes.
+    #[inline]
+    pub const fn logical_block_size(&self) -> u32 {
+        self.logical_block_size
+    }
+
+    /// Returns true if the disk is rotational.
+    #[inline]
+    pub const fn is_rotational(&self) -> bool {
+        self.rotational
+    }
+
+    /// Returns true if the device is read-only.
+    #[inline]
+    pub const fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+}
+
+impl Dev {
+    /// Returns the metadata of the block device with this id, see
+    /// [BlockDevice::from_dev].
+    #[inline]
+    pub fn block_device(&self) -> Result<BlockDevice, Errno> {
+        BlockDevice::from_dev(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sys_dev_block() {
+        assert_eq!(
+            Dev::from_split(0, 0).block_device().map(|_| ()),
+            Err(Errno::ENOENT)
+        );
+
+        let entries = match fs::read_dir("/sys/dev/block") {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        for entry in entries {
+            let entry = entry.unwrap();
+            let dev: Dev = entry.file_name().to_str().unwrap().parse().unwrap();
+            let block = dev.block_device().unwrap();
+            assert_eq!(block.dev(), dev);
+            assert!(!block.name().is_empty());
+            assert_eq!(block.is_partition(), block.name() != block.disk());
+            assert!(block.logical_block_size() >= 512);
+        }
+    }
+}
@@ -0,0 +1,369 @@
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use linux_raw_sys::general::{O_CLOEXEC, O_NOFOLLOW, O_PATH, S_ISGID, S_ISUID};
+use linux_syscalls::{syscall, Errno, Sysno};
+
+use crate::{
+    fstat, run_with_cstr, sys, CStr, Mode, Path, RawFd, Stat, StatAtFlags, CURRENT_DIRECTORY,
+};
+
+static HAS_FCHMODAT2: AtomicU8 = AtomicU8::new(2);
+
+#[inline]
+const fn mode_bits(mode: Mode) -> u32 {
+    (mode.as_u16() & 0o7777) as u32
+}
+
+/// Emulate `fchmodat2` like glibc does: open the file with `O_PATH`, refuse
+/// symbolic links and change the mode through `/proc/self/fd`.
+unsafe fn fchmodat2_fallback(
+    dirfd: RawFd,
+    path: &CStr,
+    mode: u32,
+    flags: StatAtFlags,
+) -> Result<(), Errno> {
+    let empty = flags.contains(StatAtFlags::EMPTY_PATH) && path.to_bytes().is_empty();
+
+    let fd;
+    let target = if empty && dirfd != CURRENT_DIRECTORY {
+        dirfd
+    } else {
+        let path = if empty {
+            CStr::from_bytes_with_nul_unchecked(b".\0")
+        } else {
+            path
+        };
+        let mut oflags = O_PATH | O_CLOEXEC;
+        if flags.contains(StatAtFlags::SYMLINK_NOFOLLOW) {
+            oflags |= O_NOFOLLOW;
+        }
+        fd = sys::openat(dirfd, path, oflags, 0)?;
+        fd.as_raw_fd()
+    };
+
+    if fstat(target)?.is_link() {
+        return Err(Errno::EOPNOTSUPP);
+    }
+
+    let mut buf = [0u8; 32];
+    let proc_path = sys::proc_self_fd(target, &mut buf);
+    crate::retry(|| syscall!(Sysno::fchmodat, CURRENT_DIRECTORY, proc_path.as_ptr(), mode))
+        .map(|_| ())
+}
+
+/// Change the permissions of `path` relative to `dirfd`.
+///
+/// `flags` can contain [StatAtFlags::SYMLINK_NOFOLLOW] and
+/// [StatAtFlags::EMPTY_PATH], in which case `fchmodat2` is used (Linux
+/// 6.6) with a fallback through `O_PATH` and `/proc/self/fd` on older
+/// kernels. Changing the mode of a symbolic link fails with
+/// [Errno::EOPNOTSUPP].
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+#[inline]
+pub unsafe fn fchmodat<P: AsRef<Path>>(
+    dirfd: RawFd,
+    path: P,
+    mode: Mode,
+    flags: StatAtFlags,
+) -> Result<(), Errno> {
+    run_with_cstr(path, |path| fchmodat_cstr(dirfd, path, mode, flags))
+}
+
+/// Change the permissions of `path` relative to `dirfd`.
+/// Accept `path` as a [CStr].
+///
+/// `flags` can contain [StatAtFlags::SYMLINK_NOFOLLOW] and
+/// [StatAtFlags::EMPTY_PATH], in which case `fchmodat2` is used (Linux
+/// 6.6) with a fallback through `O_PATH` and `/proc/self/fd` on older
+/// kernels. Changing the mode of a symbolic link fails with
+/// [Errno::EOPNOTSUPP].
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+pub unsafe fn fchmodat_cstr(
+    dirfd: RawFd,
+    path: &CStr,
+    mode: Mode,
+    flags: StatAtFlags,
+) -> Result<(), Errno> {
+    let mode = mode_bits(mode);
+
+    if flags == StatAtFlags::empty() {
+        return crate::retry(|| syscall!(Sysno::fchmodat, dirfd, path.as_ptr(), mode)).map(|_| ());
+    }
+    if flags.contains(StatAtFlags::NO_AUTOMOUNT) {
+        return Err(Errno::EINVAL);
+    }
+
+    match HAS_FCHMODAT2.load(Ordering::Relaxed) {
+        0 => fchmodat2_fallback(dirfd, path, mode, flags),
+        1 => crate::retry(|| syscall!(Sysno::fchmodat2, dirfd, path.as_ptr(), mode, flags.bits()))
+            .map(|_| ()),
+        _ => match crate::retry(|| {
+            syscall!(Sysno::fchmodat2, dirfd, path.as_ptr(), mode, flags.bits())
+        }) {
+            Err(Errno::ENOSYS) => {
+                HAS_FCHMODAT2.store(0, Ordering::Relaxed);
+                fchmodat2_fallback(dirfd, path, mode, flags)
+            }
+            other => {
+                HAS_FCHMODAT2.store(1, Ordering::Relaxed);
+                other.map(|_| ())
+            }
+        },
+    }
+}
+
+/// Change the permissions of the file referred by the file descriptor `fd`.
+///
+/// # Safety
+///
+/// This function is marked as unsafe because file descriptor (`fd`) cannot
+/// be checked.
+#[inline]
+pub unsafe fn fchmod(fd: RawFd, mode: Mode) -> Result<(), Errno> {
+    if fd < 0 {
+        return Err(Errno::EBADF);
+    }
+
+    crate::retry(|| syscall!(Sysno::fchmod, fd, mode_bits(mode))).map(|_| ())
+}
+
+/// Change the owner and group of `path` relative to `dirfd`, `None` leaves
+/// the corresponding id unchanged.
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+#[inline]
+pub unsafe fn fchownat<P: AsRef<Path>>(
+    dirfd: RawFd,
+    path: P,
+    uid: Option<u32>,
+    gid: Option<u32>,
+    flags: StatAtFlags,
+) -> Result<(), Errno> {
+    run_with_cstr(path, |path| fchownat_cstr(dirfd, path, uid, gid, flags))
+}
+
+/// Change the owner and group of `path` relative to `dirfd`, `None` leaves
+/// the corresponding id unchanged. Accept `path` as a [CStr].
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+#[inline]
+pub unsafe fn fchownat_cstr(
+    dirfd: RawFd,
+    path: &CStr,
+    uid: Option<u32>,
+    gid: Option<u32>,
+    flags: StatAtFlags,
+) -> Result<(), Errno> {
+    crate::retry(|| {
+        syscall!(
+            Sysno::fchownat,
+            dirfd,
+            path.as_ptr(),
+            uid.unwrap_or(u32::MAX),
+            gid.unwrap_or(u32::MAX),
+            flags.bits()
+        )
+    })
+    .map(|_| ())
+}
+
+/// Change the owner and group of the file referred by the file descriptor
+/// `fd`, `None` leaves the corresponding id unchanged.
+///
+/// # Safety
+///
+/// This function is marked as unsafe because file descriptor (`fd`) cannot
+/// be checked.
+#[inline]
+pub unsafe fn fchown(fd: RawFd, uid: Option<u32>, gid: Option<u32>) -> Result<(), Errno> {
+    if fd < 0 {
+        return Err(Errno::EBADF);
+    }
+
+    fchownat_cstr(
+        fd,
+        CStr::from_bytes_with_nul_unchecked(b"\0"),
+        uid,
+        gid,
+        StatAtFlags::EMPTY_PATH,
+    )
+}
+
+/// Copy owner, group and then mode from `stat`, like `cp --preserve`.
+///
+/// If the owner cannot be changed ([Errno::EPERM] for unprivileged users)
+/// only the group is copied, and the set-user-ID and set-group-ID bits are
+/// dropped for the ids that were not copied. Symbolic links keep their mode.
+fn copy_owner_and_mode_with<O, M>(stat: &Stat, chown: O, chmod: M) -> Result<(), Errno>
+where
+    O: Fn(Option<u32>, Option<u32>) -> Result<(), Errno>,
+    M: FnOnce(Mode) -> Result<(), Errno>,
+{
+    let mut omit = 0u16;
+    match chown(Some(stat.uid()), Some(stat.gid())) {
+        Ok(()) => (),
+        Err(Errno::EPERM | Errno::EINVAL) => {
+            omit |= S_ISUID as u16;
+            if chown(None, Some(stat.gid())).is_err() {
+                omit |= S_ISGID as u16;
+            }
+        }
+        Err(err) => return Err(err),
+    }
+
+    if stat.is_link() {
+        Ok(())
+    } else {
+        chmod(Mode::from_u16(stat.mode().as_u16() & !omit))
+    }
+}
+
+/// Copy owner, group and mode from `stat` to `path` relative to `dirfd`, like
+/// `cp --preserve=mode,ownership`.
+///
+/// If the owner cannot be changed ([Errno::EPERM] for unprivileged users)
+/// only the group is copied, and the set-user-ID and set-group-ID bits are
+/// dropped for the ids that were not copied. With
+/// [StatAtFlags::SYMLINK_NOFOLLOW] symbolic links are changed instead of
+/// their target and keep their mode.
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+#[inline]
+pub unsafe fn copy_owner_and_mode<P: AsRef<Path>>(
+    dirfd: RawFd,
+    path: P,
+    stat: &Stat,
+    flags: StatAtFlags,
+) -> Result<(), Errno> {
+    run_with_cstr(path, |path| {
+        copy_owner_and_mode_cstr(dirfd, path, stat, flags)
+    })
+}
+
+/// Copy owner, group and mode from `stat` to `path` relative to `dirfd`, like
+/// `cp --preserve=mode,ownership`. Accept `path` as a [CStr].
+///
+/// See [crate::copy_owner_and_mode].
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+pub unsafe fn copy_owner_and_mode_cstr(
+    dirfd: RawFd,
+    path: &CStr,
+    stat: &Stat,
+    flags: StatAtFlags,
+) -> Result<(), Errno> {
+    copy_owner_and_mode_with(
+        stat,
+        |uid, gid| fchownat_cstr(dirfd, path, uid, gid, flags),
+        |mode| fchmodat_cstr(dirfd, path, mode, flags),
+    )
+}
+
+/// Copy owner, group and mode from `stat` to the file referred by the file
+/// descriptor `fd`.
+///
+/// See [crate::copy_owner_and_mode].
+///
+/// # Safety
+///
+/// This function is marked as unsafe because file descriptor (`fd`) cannot
+/// be checked.
+pub unsafe fn fcopy_owner_and_mode(fd: RawFd, stat: &Stat) -> Result<(), Errno> {
+    copy_owner_and_mode_with(
+        stat,
+        |uid, gid| fchown(fd, uid, gid),
+        |mode| fchmod(fd, mode),
+    )
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::retry;
+
+    #[test]
+    fn change_mode_and_owner() {
+        linux_syscalls::init();
+
+        let dir = crate::tests::TempDir::new("chmod");
+        let src = dir.join("src");
+        let dst = dir.join("dst");
+        let link = dir.join("link");
+        std::fs::write(&src, b"").unwrap();
+        std::fs::write(&dst, b"").unwrap();
+        std::os::unix::fs::symlink(&src, &link).unwrap();
+
+        let mode = Mode::from_u16(0o640);
+        retry(|| unsafe { fchmodat(CURRENT_DIRECTORY, &src, mode, StatAtFlags::empty()) }).unwrap();
+        assert_eq!(crate::stat(&src).unwrap().mode(), mode);
+
+        let mode = Mode::from_u16(0o604);
+        retry(|| unsafe { fchmodat(CURRENT_DIRECTORY, &src, mode, StatAtFlags::SYMLINK_NOFOLLOW) })
+            .unwrap();
+        assert_eq!(crate::stat(&src).unwrap().mode(), mode);
+
+        for nofollow in [
+            |path: &CStr| unsafe {
+                fchmodat_cstr(
+                    CURRENT_DIRECTORY,
+                    path,
+                    Mode::from_u16(0o600),
+                    StatAtFlags::SYMLINK_NOFOLLOW,
+                )
+            },
+            |path: &CStr| unsafe {
+                fchmodat2_fallback(
+                    CURRENT_DIRECTORY,
+                    path,
+                    0o600,
+                    StatAtFlags::SYMLINK_NOFOLLOW,
+                )
+            },
+        ] {
+            assert_eq!(
+                retry(|| run_with_cstr(&link, nofollow)),
+                Err(Errno::EOPNOTSUPP)
+            );
+        }
+        assert_eq!(crate::stat(&src).unwrap().mode(), mode);
+
+        retry(|| unsafe {
+            run_with_cstr(&src, |path| {
+                fchmodat2_fallback(CURRENT_DIRECTORY, path, 0o644, StatAtFlags::empty())
+            })
+        })
+        .unwrap();
+        assert_eq!(crate::stat(&src).unwrap().mode(), Mode::from_u16(0o644));
+
+        let stat = crate::stat(&src).unwrap();
+        retry(|| unsafe { fchownat(CURRENT_DIRECTORY, &dst, None, None, StatAtFlags::empty()) })
+            .unwrap();
+        retry(|| unsafe {
+            copy_owner_and_mode(CURRENT_DIRECTORY, &dst, &stat, StatAtFlags::empty())
+        })
+        .unwrap();
+        let copied = crate::stat(&dst).unwrap();
+        assert_eq!(copied.mode(), stat.mode());
+        assert_eq!(copied.gid(), stat.gid());
+    }
+}
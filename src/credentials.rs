@@ -0,0 +1,277 @@
+use linux_syscalls::bitflags;
+
+use crate::{FileType, Mode, ModePermission, Stat};
+
+bitflags! {
+    /// Capabilities relevant to file permission checks.
+    #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub enum Capabilities : u8 {
+        /// `CAP_DAC_OVERRIDE`: bypass read, write and execute permission
+        /// checks. Files other than directories are executable only if at
+        /// least one of their execute bits is set.
+        DAC_OVERRIDE = 1 << 0,
+        /// `CAP_DAC_READ_SEARCH`: bypass read permission checks, and read
+        /// and execute (search) permission checks on directories.
+        DAC_READ_SEARCH = 1 << 1,
+        /// `CAP_FOWNER`: bypass the checks requiring the file owner (e.g.
+        /// changing mode or timestamps).
+        FOWNER = 1 << 2,
+    }
+}
+
+/// Credentials of a principal (filesystem user id, filesystem group id,
+/// supplementary groups and capabilities) to evaluate file permissions like
+/// the kernel does.
+///
+/// Capabilities are assumed to be held in the initial user namespace and
+/// ACLs, LSMs and filesystem flags (e.g. immutable, read-only mounts) are not
+/// considered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Credentials<'a> {
+    uid: u32,
+    gid: u32,
+    groups: &'a [u32],
+    capabilities: Capabilities,
+}
+
+impl<'a> Credentials<'a> {
+    /// Create new [Credentials] with no supplementary groups and no
+    /// capabilities.
+    #[inline]
+    pub const fn new(uid: u32, gid: u32) -> Self {
+        Self {
+            uid,
+            gid,
+            groups: &[],
+            capabilities: Capabilities::empty(),
+        }
+    }
+
+    /// Returns the credentials of root (uid and gid `0`) with all the
+    /// [Capabilities].
+    #[inline]
+    pub const fn root() -> Self {
+        Self::new(0, 0).with_capabilities(Capabilities::all())
+    }
+
+    /// Returns the credentials with `groups` as supplementary groups.
+    #[inline]
+    pub const fn with_groups(self, groups: &'a [u32]) -> Self {
+        Self {
+            uid: self.uid,
+            gid: self.gid,
+            groups,
+            capabilities: self.capabilities,
+        }
+    }
+
+    /// Returns the credentials with `capabilities`.
+    #[inline]
+    pub const fn with_capabilities(self, capabilities: Capabilities) -> Self {
+        Self {
+            uid: self.uid,
+            gid: self.gid,
+            groups: self.groups,
+            capabilities,
+        }
+    }
+
+    /// Returns the user id.
+    #[inline]
+    pub const fn uid(&self) -> u32 {
+        self.uid
+    }
+
+    /// Returns the primary group id.
+    #[inline]
+    pub const fn gid(&self) -> u32 {
+        self.gid
+    }
+
+    /// Returns the supplementary groups.
+    #[inline]
+    pub const fn groups(&self) -> &'a [u32] {
+        self.groups
+    }
+
+    /// Returns the capabilities.
+    #[inline]
+    pub const fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    /// Returns true if `gid` is the primary group or one of the
+    /// supplementary groups.
+    #[inline]
+    pub fn in_group(&self, gid: u32) -> bool {
+        self.gid == gid || self.groups.contains(&gid)
+    }
+
+    /// Returns true if all the `requested` permissions are granted on a file
+    /// owned by `uid` and `gid` with `mode` and `file_type`, like the
+    /// kernel's `generic_permission`.
+    ///
+    /// Only the permission class matching the credentials is checked (the
+    /// owner is not granted the group permissions), then
+    /// [Capabilities::DAC_READ_SEARCH] and [Capabilities::DAC_OVERRIDE] are
+    /// considered for the whole request.
+    pub fn permits_raw(
+        &self,
+        uid: u32,
+        gid: u32,
+        mode: Mode,
+        file_type: FileType,
+        requested: ModePermission,
+    ) -> bool {
+        const READ: u8 = ModePermission::READ.bits();
+        const WRITE: u8 = ModePermission::WRITE.bits();
+        const EXEC: u8 = ModePermission::EXEC.bits();
+
+        let granted = if self.uid == uid {
+            mode.owner()
+        } else if self.in_group(gid) {
+            mode.group()
+        } else {
+            mode.other()
+        };
+        let requested = requested.bits();
+        if requested & !granted.bits() == 0 {
+            return true;
+        }
+
+        let caps = self.capabilities;
+        if file_type == FileType::Directory {
+            return (requested & WRITE == 0 && caps.contains(Capabilities::DAC_READ_SEARCH))
+                || caps.contains(Capabilities::DAC_OVERRIDE);
+        }
+
+        if requested == READ && caps.contains(Capabilities::DAC_READ_SEARCH) {
+            return true;
+        }
+        // Root needs at least one execute bit to execute a file.
+        let any_exec = (mode.owner() | mode.group() | mode.other()).contains(ModePermission::EXEC);
+        (requested & EXEC == 0 || any_exec) && caps.contains(Capabilities::DAC_OVERRIDE)
+    }
+
+    /// Returns true if all the `requested` permissions are granted on the file
+    /// described by `stat`, see [Credentials::permits_raw].
+    #[inline]
+    pub fn permits(&self, stat: &Stat, requested: ModePermission) -> bool {
+        self.permits_raw(
+            stat.uid(),
+            stat.gid(),
+            stat.mode(),
+            stat.file_type(),
+            requested,
+        )
+    }
+
+    /// Returns the permissions granted on the file described by `stat`, each
+    /// checked on its own like `access(2)` with a single mode.
+    pub fn permissions(&self, stat: &Stat) -> ModePermission {
+        let mut res = ModePermission::empty();
+        for perm in [
+            ModePermission::READ,
+            ModePermission::WRITE,
+            ModePermission::EXEC,
+        ] {
+            if self.permits(stat, perm) {
+                res.insert(perm);
+            }
+        }
+        res
+    }
+
+    /// Returns true if the credentials own the file described by `stat` or
+    /// have [Capabilities::FOWNER], like the kernel's
+    /// `inode_owner_or_capable` (e.g. to change its mode).
+    #[inline]
+    pub fn is_owner_or_capable(&self, stat: &Stat) -> bool {
+        self.uid == stat.uid() || self.capabilities.contains(Capabilities::FOWNER)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permits() {
+        use FileType::{Directory, Regular};
+
+        const R: u8 = 0o4;
+        const W: u8 = 0o2;
+        const X: u8 = 0o1;
+        const OVERRIDE: u8 = 1 << 0;
+        const READ_SEARCH: u8 = 1 << 1;
+
+        let groups = [20, 30];
+        // (uid, gid, capabilities, file owner, file group, mode, file type,
+        // requested, expected)
+        #[rustfmt::skip]
+        let cases = [
+            (1000, 100, 0, 1000, 100, 0o600, Regular, R | W, true),
+            (1000, 100, 0, 1000, 100, 0o600, Regular, X, false),
+            // The owner class is used even if the group grants more.
+            (1000, 100, 0, 1000, 100, 0o070, Regular, R, false),
+            (1000, 100, 0, 0, 100, 0o640, Regular, R, true),
+            (1000, 100, 0, 0, 100, 0o640, Regular, W, false),
+            // Supplementary groups.
+            (1000, 100, 0, 0, 30, 0o640, Regular, R, true),
+            (1000, 100, 0, 0, 40, 0o640, Regular, R, false),
+            (1000, 100, 0, 0, 40, 0o644, Regular, R, true),
+            // Group class is used even if other grants more.
+            (1000, 100, 0, 0, 20, 0o604, Regular, R, false),
+            (1000, 100, 0, 0, 0, 0o755, Directory, R | X, true),
+            (1000, 100, 0, 0, 0, 0o755, Directory, W, false),
+            (1000, 100, 0, 0, 0, 0, Regular, 0, true),
+            // CAP_DAC_READ_SEARCH.
+            (1000, 100, READ_SEARCH, 0, 0, 0o000, Regular, R, true),
+            (1000, 100, READ_SEARCH, 0, 0, 0o000, Regular, R | X, false),
+            (1000, 100, READ_SEARCH, 0, 0, 0o000, Regular, W, false),
+            (1000, 100, READ_SEARCH, 0, 0, 0o000, Directory, R | X, true),
+            (1000, 100, READ_SEARCH, 0, 0, 0o000, Directory, W | X, false),
+            // Capabilities apply to the whole request.
+            (1000, 100, READ_SEARCH, 0, 0, 0o002, Regular, R | W, false),
+            // CAP_DAC_OVERRIDE.
+            (0, 0, OVERRIDE, 1000, 100, 0o000, Regular, R | W, true),
+            (0, 0, OVERRIDE, 1000, 100, 0o000, Regular, X, false),
+            (0, 0, OVERRIDE, 1000, 100, 0o001, Regular, X, true),
+            (0, 0, OVERRIDE, 1000, 100, 0o010, Regular, R | W | X, true),
+            (0, 0, OVERRIDE, 1000, 100, 0o000, Directory, R | W | X, true),
+            // Root without capabilities is an ordinary user.
+            (0, 0, 0, 1000, 100, 0o600, Regular, R, false),
+        ];
+        for (i, (uid, gid, caps, owner, group, mode, file_type, requested, expected)) in
+            cases.into_iter().enumerate()
+        {
+            let creds = Credentials::new(uid, gid)
+                .with_groups(&groups)
+                .with_capabilities(Capabilities::from_bits(caps));
+            assert_eq!(
+                creds.permits_raw(
+                    owner,
+                    group,
+                    Mode::from_u16(mode),
+                    file_type,
+                    ModePermission::from_bits(requested)
+                ),
+                expected,
+                "case {}",
+                i
+            );
+        }
+    }
+
+    #[test]
+    fn dev_null_permissions() {
+        linux_syscalls::init();
+
+        let stat = crate::retry(|| crate::stat(crate::tests::dev_null())).unwrap();
+        let all = ModePermission::READ | ModePermission::WRITE;
+        assert_eq!(Credentials::root().permissions(&stat), all);
+        assert_eq!(Credentials::new(65534, 65534).permissions(&stat), all);
+        assert!(Credentials::root().is_owner_or_capable(&stat));
+        assert!(!Credentials::new(65534, 65534).is_owner_or_capable(&stat));
+    }
+}
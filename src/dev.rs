@@ -1,6 +1,6 @@
 #![allow(clippy::partialeq_ne_impl)]
 
-use core::fmt;
+use core::{fmt, str::FromStr};
 
 /// Device ID representation backed by an u32.
 #[repr(transparent)]
@@ -17,13 +17,13 @@ impl Dev32 {
     /// Returns device major.
     #[inline]
     pub const fn major(&self) -> u32 {
-        (self.0 >> 8) & 0xff
+        (self.0 & 0xfff00) >> 8
     }
 
     /// Returns device minor.
     #[inline]
     pub const fn minor(&self) -> u32 {
-        (self.0 >> 19) | (self.0 & 0xff)
+        (self.0 & 0xff) | ((self.0 >> 12) & 0xfff00)
     }
 
     /// Returns device id as a u32.
@@ -53,6 +53,20 @@ impl fmt::Debug for Dev32 {
     }
 }
 
+impl fmt::Display for Dev32 {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.major(), self.minor())
+    }
+}
+
+impl fmt::LowerHex for Dev32 {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.as_u64(), f)
+    }
+}
+
 impl PartialEq for Dev32 {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
@@ -467,6 +481,20 @@ impl fmt::Debug for DevSplit {
     }
 }
 
+impl fmt::Display for DevSplit {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.major(), self.minor())
+    }
+}
+
+impl fmt::LowerHex for DevSplit {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.as_u64(), f)
+    }
+}
+
 impl core::hash::Hash for DevSplit {
     #[inline]
     fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
@@ -898,6 +926,20 @@ impl fmt::Debug for Dev64 {
     }
 }
 
+impl fmt::Display for Dev64 {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.major(), self.minor())
+    }
+}
+
+impl fmt::LowerHex for Dev64 {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.as_u64(), f)
+    }
+}
+
 impl core::hash::Hash for Dev64 {
     #[inline]
     fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
@@ -1301,7 +1343,11 @@ impl Dev {
         }
     }
 
-    /// Returns device id as a u64.
+    /// Returns device id as a u64 (glibc `makedev` layout).
+    ///
+    /// This round-trips with [Dev::from_u64] for every major and minor:
+    /// `Dev::from_u64(Dev::from_split(major, minor).as_u64())` has the same
+    /// `major` and `minor`.
     #[inline]
     pub const fn as_u64(&self) -> u64 {
         match self {
@@ -1317,7 +1363,7 @@ impl Dev {
         Self::B32(Dev32::new(value))
     }
 
-    /// Create a [Dev] from a u64.
+    /// Create a [Dev] from a u64 (glibc `makedev` layout).
     #[inline]
     pub const fn from_u64(value: u64) -> Self {
         Self::B64(Dev64::new(value))
@@ -1330,6 +1376,53 @@ impl Dev {
     }
 }
 
+/// Encode a device id like the kernel's `old_encode_dev`, an 8-bit major and
+/// an 8-bit minor packed in a u16. Higher bits are truncated.
+#[inline]
+pub const fn old_encode_dev(dev: Dev) -> u16 {
+    (((dev.major() & 0xff) << 8) | (dev.minor() & 0xff)) as u16
+}
+
+/// Decode a device id like the kernel's `old_decode_dev`.
+#[inline]
+pub const fn old_decode_dev(value: u16) -> Dev {
+    Dev::from_split((value >> 8) as u32, (value & 0xff) as u32)
+}
+
+/// Encode a device id like the kernel's `new_encode_dev`, a 12-bit major and
+/// a 20-bit minor packed in a u32. Higher bits are truncated.
+#[inline]
+pub const fn new_encode_dev(dev: Dev) -> u32 {
+    let major = dev.major() & 0xfff;
+    let minor = dev.minor() & 0xfffff;
+    (minor & 0xff) | (major << 8) | ((minor & !0xff) << 12)
+}
+
+/// Decode a device id like the kernel's `new_decode_dev`.
+#[inline]
+pub const fn new_decode_dev(value: u32) -> Dev {
+    Dev::from_split(
+        (value & 0xfff00) >> 8,
+        (value & 0xff) | ((value >> 12) & 0xfff00),
+    )
+}
+
+/// Encode a device id like the kernel's `huge_encode_dev`, same as
+/// [new_encode_dev] widened to a u64.
+///
+/// For a 12-bit major and a 20-bit minor this is the same value returned by
+/// [Dev::as_u64].
+#[inline]
+pub const fn huge_encode_dev(dev: Dev) -> u64 {
+    new_encode_dev(dev) as u64
+}
+
+/// Decode a device id like the kernel's `huge_decode_dev`.
+#[inline]
+pub const fn huge_decode_dev(value: u64) -> Dev {
+    new_decode_dev(value as u32)
+}
+
 impl fmt::Debug for Dev {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -1337,6 +1430,20 @@ impl fmt::Debug for Dev {
     }
 }
 
+impl fmt::Display for Dev {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.major(), self.minor())
+    }
+}
+
+impl fmt::LowerHex for Dev {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.as_u64(), f)
+    }
+}
+
 impl core::hash::Hash for Dev {
     #[inline]
     fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
@@ -1803,3 +1910,152 @@ impl From<Dev> for u64 {
         value.as_u64()
     }
 }
+
+/// An error which can be returned when parsing a [Dev].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDevError(());
+
+impl fmt::Display for ParseDevError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid device id")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseDevError {}
+
+impl FromStr for Dev {
+    type Err = ParseDevError;
+
+    /// Parse a [Dev] in `major:minor` notation (e.g. `8:1`), or an encoded
+    /// device id in hexadecimal with the `0x` prefix (e.g. `0x801`) or in
+    /// decimal (e.g. `2049`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn digits(s: &str, radix: u32) -> Result<&str, ParseDevError> {
+            if !s.is_empty() && s.chars().all(|c| c.is_digit(radix)) {
+                Ok(s)
+            } else {
+                Err(ParseDevError(()))
+            }
+        }
+
+        if let Some((major, minor)) = s.split_once(':') {
+            let major = u32::from_str(digits(major, 10)?).map_err(|_| ParseDevError(()))?;
+            let minor = u32::from_str(digits(minor, 10)?).map_err(|_| ParseDevError(()))?;
+            Ok(Self::from_split(major, minor))
+        } else if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            u64::from_str_radix(digits(hex, 16)?, 16)
+                .map(Self::from_u64)
+                .map_err(|_| ParseDevError(()))
+        } else {
+            u64::from_str(digits(s, 10)?)
+                .map(Self::from_u64)
+                .map_err(|_| ParseDevError(()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn display_and_hex() {
+        let dev = Dev::from_split(8, 1);
+        assert_eq!(dev.to_string(), "8:1");
+        assert_eq!(format!("{:x}", dev), "801");
+        assert_eq!(format!("{:#x}", dev), "0x801");
+        assert_eq!(Dev64::new(0x801).to_string(), "8:1");
+        assert_eq!(Dev32::new(0x801).to_string(), "8:1");
+        assert_eq!(DevSplit::new(259, 65536).to_string(), "259:65536");
+
+        let dev = Dev::from_split(259, 65536);
+        assert_eq!(dev.to_string().parse::<Dev>(), Ok(dev));
+        assert_eq!(format!("{:#x}", dev).parse::<Dev>(), Ok(dev));
+        assert_eq!(dev.as_u64().to_string().parse::<Dev>(), Ok(dev));
+
+        let dev32 = Dev32::new(new_encode_dev(dev));
+        assert_eq!(format!("{:x}", dev32), format!("{:x}", dev));
+        assert_eq!(format!("{:#x}", dev32).parse::<Dev>(), Ok(dev));
+        assert_eq!(dev32.to_string().parse::<Dev>(), Ok(dev));
+    }
+
+    #[test]
+    fn kernel_encoding_round_trip() {
+        fn check(major: u32, minor: u32) {
+            let dev = Dev::from_split(major, minor);
+            assert_eq!(new_decode_dev(new_encode_dev(dev)), dev);
+            assert_eq!(Dev32::new(new_encode_dev(dev)).major(), major);
+            assert_eq!(Dev32::new(new_encode_dev(dev)).minor(), minor);
+            assert_eq!(huge_decode_dev(huge_encode_dev(dev)), dev);
+            assert_eq!(huge_encode_dev(dev), dev.as_u64());
+            assert_eq!(Dev::from_u64(dev.as_u64()), dev);
+            assert_eq!(Dev64::new(dev.as_u64()).major(), major);
+            assert_eq!(Dev64::new(dev.as_u64()).minor(), minor);
+            if major < 0x100 && minor < 0x100 {
+                assert_eq!(old_decode_dev(old_encode_dev(dev)), dev);
+            }
+        }
+
+        // Every major with a spread of minors, including the boundaries.
+        let mut x = 0x2545_f491u32;
+        for major in 0..0x1000 {
+            for minor in [0, 0xff, 0x100, 0xfffff] {
+                check(major, minor);
+            }
+            for _ in 0..16 {
+                x ^= x << 13;
+                x ^= x >> 17;
+                x ^= x << 5;
+                check(major, x & 0xfffff);
+            }
+        }
+
+        // Every minor with a spread of majors, including the boundaries.
+        for minor in 0..0x100000 {
+            for major in [0, 0xff, 0x100, 0xfff] {
+                check(major, minor);
+            }
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            check(x & 0xfff, minor);
+        }
+
+        assert_eq!(old_encode_dev(Dev::from_split(1, 3)), 0x103);
+        assert_eq!(new_encode_dev(Dev::from_split(259, 0x12345)), 0x12310345);
+        assert_eq!(new_decode_dev(0x12310345), Dev::from_split(259, 0x12345));
+    }
+
+    #[test]
+    fn mips_stat64_dev() {
+        // `st_dev` of a file on /dev/nvme0n1p1 (259:1) as filled in by
+        // cp_new_stat64 on mips, which only has 32 bits for it.
+        let dev = Dev::B32(Dev32::new(0x10301));
+        assert_eq!((dev.major(), dev.minor()), (259, 1));
+        assert_eq!(dev, Dev::from_split(259, 1));
+
+        // Minors above 0xff spill into the top bits (/dev/sdq1 is 65:257).
+        let dev = Dev::B32(Dev32::new(0x10_4101));
+        assert_eq!((dev.major(), dev.minor()), (65, 257));
+        assert_eq!(new_decode_dev(0x10_4101), dev);
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!("8:1".parse::<Dev>(), Ok(Dev::from_split(8, 1)));
+        assert_eq!("0x801".parse::<Dev>(), Ok(Dev::from_split(8, 1)));
+        assert_eq!("0X801".parse::<Dev>(), Ok(Dev::from_split(8, 1)));
+        assert_eq!("2049".parse::<Dev>(), Ok(Dev::from_split(8, 1)));
+
+        for s in [
+            "", ":", "8:", ":1", "8:1:2", "+8:1", "8:-1", "0x", "0x+1", "-1", "8 1", "a",
+        ] {
+            assert_eq!(s.parse::<Dev>(), Err(ParseDevError(())), "{:?}", s);
+        }
+        assert!("4294967296:0".parse::<Dev>().is_err());
+        assert!("18446744073709551616".parse::<Dev>().is_err());
+    }
+}
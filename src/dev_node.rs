@@ -0,0 +1,215 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use linux_syscalls::Errno;
+
+use crate::{fstat, lstat, stat, Dev, FileType, RawFd, Timestamp};
+
+/// Directories under `/dev` that never contain device nodes.
+const SKIP: &[&str] = &["fd", "hugepages", "mqueue", "shm"];
+
+/// Result of a lookup.
+enum Cached {
+    Node(PathBuf),
+    /// No node was found while `/dev` had this modification time.
+    Missing(Timestamp),
+}
+
+static CACHE: Mutex<Vec<((Dev, FileType), Cached)>> = Mutex::new(Vec::new());
+
+#[inline]
+fn dev_mtime() -> Option<Timestamp> {
+    stat("/dev").map(|stat| stat.mtime()).ok()
+}
+
+#[inline]
+fn matches(path: &Path, dev: Dev, file_type: FileType) -> bool {
+    lstat(path).map_or(false, |stat| {
+        stat.file_type() == file_type && stat.rdev() == dev
+    })
+}
+
+fn scan(dir: &Path, dev: Dev, file_type: FileType) -> Option<PathBuf> {
+    let mut subdirs = Vec::new();
+
+    for entry in fs::read_dir(dir).ok()?.flatten() {
+        let path = entry.path();
+        let stat = match lstat(&path) {
+            Ok(stat) => stat,
+            Err(_) => continue,
+        };
+
+        if stat.is_dir() {
+            if !SKIP.iter().any(|skip| entry.file_name() == *skip) {
+                subdirs.push(path);
+            }
+        } else if stat.file_type() == file_type && stat.rdev() == dev {
+            return Some(path);
+        }
+    }
+
+    subdirs.sort();
+    subdirs
+        .into_iter()
+        .find_map(|subdir| scan(&subdir, dev, file_type))
+}
+
+fn lookup(dev: Dev, file_type: FileType) -> Option<PathBuf> {
+    // /dev/block and /dev/char are maintained by udev and point directly to
+    // the right node.
+    let link = format!(
+        "/dev/{}/{}:{}",
+        if file_type == FileType::Block {
+            "block"
+        } else {
+            "char"
+        },
+        dev.major(),
+        dev.minor()
+    );
+    if stat(&link).map_or(false, |stat| {
+        stat.file_type() == file_type && stat.rdev() == dev
+    }) {
+        if let Ok(path) = fs::canonicalize(&link) {
+            if matches(&path, dev, file_type) {
+                return Some(path);
+            }
+        }
+    }
+
+    scan(Path::new("/dev"), dev, file_type)
+}
+
+/// Returns the path of the device node under `/dev` for the device `dev` of
+/// type `file_type` ([FileType::Block] or [FileType::Character]).
+///
+/// The nodes are matched with [crate::lstat] on `rdev` and file type, the
+/// result is cached and revalidated on each call. When no node is found,
+/// [Errno::ENOENT] is cached until the modification time of `/dev` changes.
+///
+/// Returns [Errno::ENOENT] without looking for devices with major 0, used
+/// for filesystems without a backing device (e.g. tmpfs, overlayfs or btrfs
+/// subvolumes).
+pub fn find_device_node(dev: Dev, file_type: FileType) -> Result<PathBuf, Errno> {
+    if file_type != FileType::Block && file_type != FileType::Character {
+        return Err(Errno::EINVAL);
+    }
+    if dev.major() == 0 {
+        return Err(Errno::ENOENT);
+    }
+
+    let key = (dev, file_type);
+    {
+        let mut cache = CACHE.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(i) = cache.iter().position(|(k, _)| *k == key) {
+            match cache[i].1 {
+                Cached::Node(ref path) if matches(path, dev, file_type) => return Ok(path.clone()),
+                Cached::Missing(mtime) if dev_mtime() == Some(mtime) => return Err(Errno::ENOENT),
+                _ => (),
+            }
+            cache.swap_remove(i);
+        }
+    }
+
+    // Taken before the scan, so nodes created during the scan invalidate the
+    // cached result.
+    let mtime = dev_mtime();
+    let (res, cached) = match lookup(dev, file_type) {
+        Some(path) => (Ok(path.clone()), Cached::Node(path)),
+        None => match mtime {
+            Some(mtime) => (Err(Errno::ENOENT), Cached::Missing(mtime)),
+            None => return Err(Errno::ENOENT),
+        },
+    };
+
+    let mut cache = CACHE.lock().unwrap_or_else(|err| err.into_inner());
+    match cache.iter_mut().find(|(k, _)| *k == key) {
+        Some(entry) => entry.1 = cached,
+        None => cache.push((key, cached)),
+    }
+    res
+}
+
+impl Dev {
+    /// Returns the path of the device node under `/dev` for this device, see
+    /// [crate::find_device_node].
+    #[inline]
+    pub fn device_node(&self, file_type: FileType) -> Result<PathBuf, Errno> {
+        find_device_node(*self, file_type)
+    }
+}
+
+/// Returns the path of the device node under `/dev` for the file descriptor
+/// `fd`.
+///
+/// If `fd` refers to a block or character device its `rdev` is looked up
+/// (like `ttyname`), otherwise the block device on which the file resides,
+/// [Errno::ENOENT] is returned without scanning `/dev` for files on
+/// filesystems without a backing device (major 0).
+///
+/// # Safety
+///
+/// This function is marked as unsafe because file descriptor (`fd`) cannot
+/// be checked.
+pub unsafe fn fd_device_node(fd: RawFd) -> Result<PathBuf, Errno> {
+    let stat = fstat(fd)?;
+    match stat.file_type() {
+        file_type @ (FileType::Block | FileType::Character) => {
+            find_device_node(stat.rdev(), file_type)
+        }
+        _ => find_device_node(stat.dev(), FileType::Block),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dev_null_node() {
+        linux_syscalls::init();
+
+        let stat = crate::retry(|| crate::stat(crate::tests::dev_null())).unwrap();
+        assert_eq!(
+            find_device_node(stat.rdev(), FileType::Character),
+            Ok(PathBuf::from("/dev/null"))
+        );
+        // Cached.
+        assert_eq!(
+            stat.rdev().device_node(FileType::Character),
+            Ok(PathBuf::from("/dev/null"))
+        );
+        assert_eq!(
+            find_device_node(stat.rdev(), FileType::Regular),
+            Err(Errno::EINVAL)
+        );
+        assert_eq!(
+            find_device_node(Dev::from_split(0, 0), FileType::Character),
+            Err(Errno::ENOENT)
+        );
+    }
+
+    #[test]
+    fn missing_node() {
+        linux_syscalls::init();
+
+        // proc has no backing device.
+        let file = std::fs::File::open("/proc/self/status").unwrap();
+        assert_eq!(
+            unsafe { fd_device_node(std::os::unix::io::AsRawFd::as_raw_fd(&file)) },
+            Err(Errno::ENOENT)
+        );
+
+        let dev = Dev::from_split(4095, 0xfffff);
+        assert_eq!(find_device_node(dev, FileType::Block), Err(Errno::ENOENT));
+        {
+            let cache = CACHE.lock().unwrap_or_else(|err| err.into_inner());
+            let cached = cache.iter().find(|(k, _)| *k == (dev, FileType::Block));
+            assert!(matches!(cached, Some((_, Cached::Missing(_)))));
+        }
+        assert_eq!(find_device_node(dev, FileType::Block), Err(Errno::ENOENT));
+    }
+}
@@ -0,0 +1,326 @@
+use crate::{Dev, FileType, Stat};
+
+/// Description of a well-known device, from the kernel's
+/// `Documentation/admin-guide/devices.txt`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DeviceKind {
+    file_type: FileType,
+    driver: &'static str,
+    description: &'static str,
+    node: Option<&'static str>,
+    prefix: Option<&'static str>,
+}
+
+impl DeviceKind {
+    const fn new(
+        file_type: FileType,
+        driver: &'static str,
+        description: &'static str,
+        node: Option<&'static str>,
+        prefix: Option<&'static str>,
+    ) -> Self {
+        Self {
+            file_type,
+            driver,
+            description,
+            node,
+            prefix,
+        }
+    }
+
+    /// Returns the device type ([FileType::Block] or [FileType::Character]).
+    #[inline]
+    pub const fn file_type(&self) -> FileType {
+        self.file_type
+    }
+
+    /// Returns the name of the driver (e.g. `mem`, `pts`, `loop`, `sd`).
+    #[inline]
+    pub const fn driver(&self) -> &'static str {
+        self.driver
+    }
+
+    /// Returns a human readable description.
+    #[inline]
+    pub const fn description(&self) -> &'static str {
+        self.description
+    }
+
+    /// Returns the node name relative to `/dev` for devices with a fixed
+    /// name (e.g. `null`, `ptmx`).
+    #[inline]
+    pub const fn node(&self) -> Option<&'static str> {
+        self.node
+    }
+
+    /// Returns the common prefix of the node names relative to `/dev` for
+    /// ranges of devices (e.g. `pts/`, `loop`, `sd`).
+    #[inline]
+    pub const fn prefix(&self) -> Option<&'static str> {
+        self.prefix
+    }
+
+    /// Returns true if the device is a terminal (virtual console, serial
+    /// port, USB serial, pseudo-terminal slave, system console or the
+    /// controlling terminal).
+    #[inline]
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self.driver,
+            "tty" | "ttyS" | "ttyACM" | "ttyUSB" | "pts" | "console"
+        )
+    }
+
+    /// Returns true if the device is a Unix98 pseudo-terminal (master or
+    /// slave).
+    #[inline]
+    pub fn is_pty(&self) -> bool {
+        matches!(self.driver, "pts" | "ptm" | "ptmx")
+    }
+}
+
+struct Entry {
+    majors: (u32, u32),
+    minors: (u32, u32),
+    kind: DeviceKind,
+}
+
+const ANY: (u32, u32) = (0, u32::MAX);
+
+macro_rules! entry {
+    ($ft:ident, $majors:expr, $minors:expr, $driver:expr, $description:expr, node = $node:expr) => {
+        Entry {
+            majors: $majors,
+            minors: $minors,
+            kind: DeviceKind::new(FileType::$ft, $driver, $description, Some($node), None),
+        }
+    };
+    ($ft:ident, $majors:expr, $minors:expr, $driver:expr, $description:expr, prefix = $prefix:expr) => {
+        Entry {
+            majors: $majors,
+            minors: $minors,
+            kind: DeviceKind::new(FileType::$ft, $driver, $description, None, Some($prefix)),
+        }
+    };
+    ($ft:ident, $majors:expr, $minors:expr, $driver:expr, $description:expr) => {
+        Entry {
+            majors: $majors,
+            minors: $minors,
+            kind: DeviceKind::new(FileType::$ft, $driver, $description, None, None),
+        }
+    };
+}
+
+/// Well-known devices, more specific entries first.
+///
+/// Dynamically allocated majors differ between systems and are listed in
+/// `DYNAMIC` instead.
+#[rustfmt::skip]
+const DEVICES: &[Entry] = &[
+    entry!(Character, (1, 1), (1, 1), "mem", "Physical memory access", node = "mem"),
+    entry!(Character, (1, 1), (2, 2), "mem", "Kernel virtual memory access", node = "kmem"),
+    entry!(Character, (1, 1), (3, 3), "mem", "Null device", node = "null"),
+    entry!(Character, (1, 1), (4, 4), "mem", "I/O port access", node = "port"),
+    entry!(Character, (1, 1), (5, 5), "mem", "Null byte source", node = "zero"),
+    entry!(Character, (1, 1), (7, 7), "mem", "Returns ENOSPC on write", node = "full"),
+    entry!(Character, (1, 1), (8, 8), "mem", "Nondeterministic random number generator", node = "random"),
+    entry!(Character, (1, 1), (9, 9), "mem", "Faster, less secure random number generator", node = "urandom"),
+    entry!(Character, (1, 1), (11, 11), "mem", "Kernel messages", node = "kmsg"),
+    entry!(Character, (1, 1), ANY, "mem", "Memory devices"),
+    entry!(Character, (4, 4), (0, 0), "tty", "Current virtual console", node = "tty0"),
+    entry!(Character, (4, 4), (1, 63), "tty", "Virtual console", prefix = "tty"),
+    entry!(Character, (4, 4), (64, 255), "ttyS", "Serial port", prefix = "ttyS"),
+    entry!(Character, (5, 5), (0, 0), "tty", "Current TTY device", node = "tty"),
+    entry!(Character, (5, 5), (1, 1), "console", "System console", node = "console"),
+    entry!(Character, (5, 5), (2, 2), "ptmx", "PTY master multiplex", node = "ptmx"),
+    entry!(Character, (5, 5), ANY, "tty", "Alternate TTY device"),
+    entry!(Character, (7, 7), (0, 63), "vcs", "Virtual console text memory", prefix = "vcs"),
+    entry!(Character, (7, 7), (128, 191), "vcs", "Virtual console text/attribute memory", prefix = "vcsa"),
+    entry!(Character, (10, 10), (200, 200), "misc", "TAP/TUN network device", node = "net/tun"),
+    entry!(Character, (10, 10), (229, 229), "misc", "FUSE filesystem", node = "fuse"),
+    entry!(Character, (10, 10), (236, 236), "misc", "Device-mapper control", node = "mapper/control"),
+    entry!(Character, (10, 10), (237, 237), "misc", "Loop control", node = "loop-control"),
+    entry!(Character, (10, 10), ANY, "misc", "Miscellaneous device"),
+    entry!(Character, (13, 13), ANY, "input", "Input device", prefix = "input/"),
+    entry!(Character, (29, 29), ANY, "fb", "Frame buffer", prefix = "fb"),
+    entry!(Character, (89, 89), ANY, "i2c", "I2C bus interface", prefix = "i2c-"),
+    entry!(Character, (128, 135), ANY, "ptm", "Unix98 PTY master"),
+    entry!(Character, (136, 143), ANY, "pts", "Unix98 PTY slave", prefix = "pts/"),
+    entry!(Character, (166, 166), ANY, "ttyACM", "ACM USB modem", prefix = "ttyACM"),
+    entry!(Character, (180, 180), ANY, "usb", "USB device"),
+    entry!(Character, (188, 188), ANY, "ttyUSB", "USB serial converter", prefix = "ttyUSB"),
+    entry!(Character, (189, 189), ANY, "usb_device", "USB device (raw)", prefix = "bus/usb/"),
+    entry!(Character, (226, 226), ANY, "drm", "Direct rendering infrastructure", prefix = "dri/"),
+    entry!(Block, (1, 1), ANY, "ramdisk", "RAM disk", prefix = "ram"),
+    entry!(Block, (2, 2), ANY, "fd", "Floppy disk", prefix = "fd"),
+    entry!(Block, (3, 3), ANY, "ide", "First MFM, RLL and IDE hard disk/CD-ROM interface", prefix = "hd"),
+    entry!(Block, (7, 7), ANY, "loop", "Loopback device", prefix = "loop"),
+    entry!(Block, (8, 8), ANY, "sd", "SCSI disk device", prefix = "sd"),
+    entry!(Block, (9, 9), ANY, "md", "Metadisk (RAID) device", prefix = "md"),
+    entry!(Block, (11, 11), ANY, "sr", "SCSI CD-ROM device", prefix = "sr"),
+    entry!(Block, (65, 71), ANY, "sd", "SCSI disk device", prefix = "sd"),
+    entry!(Block, (128, 135), ANY, "sd", "SCSI disk device", prefix = "sd"),
+    entry!(Block, (179, 179), ANY, "mmc", "MMC block device", prefix = "mmcblk"),
+    entry!(Block, (202, 202), ANY, "xvd", "Xen virtual block device", prefix = "xvd"),
+    entry!(Block, (259, 259), ANY, "blkext", "Block extended major (NVMe namespaces and extended partitions)"),
+];
+
+/// Devices with a dynamically allocated major, by the driver name registered
+/// in `/proc/devices`.
+#[cfg(feature = "std")]
+#[rustfmt::skip]
+const DYNAMIC: &[(&str, DeviceKind)] = &[
+    ("device-mapper", DeviceKind::new(FileType::Block, "dm", "Device-mapper device", None, Some("dm-"))),
+    ("nvme", DeviceKind::new(FileType::Character, "nvme", "NVMe controller", None, Some("nvme"))),
+    ("nvme-generic", DeviceKind::new(FileType::Character, "nvme-generic", "NVMe generic namespace", None, Some("ng"))),
+    ("ublk-char", DeviceKind::new(FileType::Character, "ublk-char", "Userspace block device control", None, Some("ublkc"))),
+    ("virtblk", DeviceKind::new(FileType::Block, "virtblk", "Virtio block device", None, Some("vd"))),
+    ("zram", DeviceKind::new(FileType::Block, "zram", "Compressed RAM disk", None, Some("zram"))),
+];
+
+/// NVMe namespaces, which share the block extended major 259.
+#[cfg(feature = "std")]
+const NVME_NAMESPACE: DeviceKind = DeviceKind::new(
+    FileType::Block,
+    "nvme",
+    "NVMe namespace",
+    None,
+    Some("nvme"),
+);
+
+impl Dev {
+    /// Returns the well-known device with this id and type
+    /// ([FileType::Block] or [FileType::Character]), if any.
+    ///
+    /// With the `std` feature, devices with a dynamically allocated major
+    /// (device-mapper, zram, NVMe controllers, ...) are recognized by the
+    /// driver name registered in `/proc/devices`, and NVMe namespaces by
+    /// their name in sysfs.
+    pub fn describe(&self, file_type: FileType) -> Option<DeviceKind> {
+        let kind = self.describe_static(file_type);
+        #[cfg(feature = "std")]
+        if kind.map_or(true, |kind| kind.driver == "blkext") {
+            if let Some(kind) = self.describe_dynamic(file_type) {
+                return Some(kind);
+            }
+        }
+        kind
+    }
+
+    /// Like [Dev::describe] but only looks up the built-in table, without
+    /// reading any file.
+    pub(crate) fn describe_static(&self, file_type: FileType) -> Option<DeviceKind> {
+        let (major, minor) = (self.major(), self.minor());
+        DEVICES
+            .iter()
+            .find(|e| {
+                e.kind.file_type == file_type
+                    && (e.majors.0..=e.majors.1).contains(&major)
+                    && (e.minors.0..=e.minors.1).contains(&minor)
+            })
+            .map(|e| e.kind)
+    }
+
+    #[cfg(feature = "std")]
+    fn describe_dynamic(&self, file_type: FileType) -> Option<DeviceKind> {
+        if file_type == FileType::Block && self.major() == 259 {
+            return self
+                .block_device()
+                .ok()
+                .filter(|block| block.disk().starts_with("nvme"))
+                .map(|_| NVME_NAMESPACE);
+        }
+
+        crate::ProcDevices::with_cached(|devices| {
+            let driver = devices.driver(self.major(), file_type)?;
+            DYNAMIC
+                .iter()
+                .find(|(name, kind)| *name == driver && kind.file_type == file_type)
+                .map(|(_, kind)| *kind)
+        })
+        .ok()
+        .flatten()
+    }
+}
+
+impl Stat {
+    /// Returns the well-known device this file represents if the file is of
+    /// block or character device type, see [Dev::describe].
+    #[inline]
+    pub fn device_kind(&self) -> Option<DeviceKind> {
+        match self.file_type() {
+            file_type @ (FileType::Block | FileType::Character) => self.rdev().describe(file_type),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn driver(major: u32, minor: u32, file_type: FileType) -> Option<&'static str> {
+        Dev::from_split(major, minor)
+            .describe(file_type)
+            .map(|kind| kind.driver())
+    }
+
+    #[test]
+    fn registry() {
+        let null = Dev::from_split(1, 3).describe(FileType::Character).unwrap();
+        assert_eq!(null.driver(), "mem");
+        assert_eq!(null.node(), Some("null"));
+
+        let pts = Dev::from_split(136, 4).describe(FileType::Character);
+        assert!(pts.map_or(false, |pts| pts.is_pty() && pts.is_terminal()));
+        assert_eq!(pts.and_then(|pts| pts.prefix()), Some("pts/"));
+        assert_eq!(
+            Dev::from_split(143, 1 << 19).describe(FileType::Character),
+            pts
+        );
+
+        let tty = Dev::from_split(4, 1).describe(FileType::Character);
+        assert!(tty.map_or(false, |tty| tty.is_terminal() && !tty.is_pty()));
+
+        assert_eq!(driver(1, 3, FileType::Block), Some("ramdisk"));
+        assert_eq!(driver(4, 64, FileType::Character), Some("ttyS"));
+        assert_eq!(driver(7, 3, FileType::Block), Some("loop"));
+        assert_eq!(driver(66, 0, FileType::Block), Some("sd"));
+        assert_eq!(driver(10, 1, FileType::Character), Some("misc"));
+        assert_eq!(driver(0, 0, FileType::Character), None);
+        assert_eq!(driver(5, 0, FileType::Character), Some("tty"));
+        assert_eq!(driver(5, 2, FileType::Character), Some("ptmx"));
+        assert_eq!(driver(5, 3, FileType::Character), Some("tty"));
+        assert_eq!(driver(5, 64, FileType::Character), Some("tty"));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn dynamic_majors() {
+        let devices = crate::ProcDevices::read().unwrap();
+        for (file_type, section) in [
+            (FileType::Character, devices.character()),
+            (FileType::Block, devices.block()),
+        ] {
+            for (major, name) in section {
+                let expected = DYNAMIC
+                    .iter()
+                    .find(|(n, kind)| n == name && kind.file_type == file_type);
+                if let Some((_, expected)) = expected {
+                    assert_eq!(
+                        Dev::from_split(*major, 0).describe(file_type),
+                        Some(*expected)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn dev_null_kind() {
+        linux_syscalls::init();
+
+        let stat = crate::retry(|| crate::stat(crate::tests::dev_null())).unwrap();
+        assert_eq!(stat.device_kind().and_then(|k| k.node()), Some("null"));
+    }
+}
@@ -0,0 +1,184 @@
+use alloc::vec::Vec;
+
+use linux_raw_sys::general::{O_CLOEXEC, O_DIRECTORY, O_RDONLY};
+use linux_syscalls::{syscall, Errno, Sysno};
+
+use crate::{run_with_cstr, sys, CStr, CString, FileType, Path, RawFd};
+
+/// Size of the buffer passed to `getdents64`.
+const BUF_SIZE: usize = 32 * 1024;
+
+// Offsets of the fields of `struct linux_dirent64`.
+const D_INO: usize = 0;
+const D_RECLEN: usize = 16;
+const D_TYPE: usize = 18;
+const D_NAME: usize = 19;
+
+/// Entry of a directory returned by [crate::list_dir].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DirEntry {
+    ino: u64,
+    file_type: FileType,
+    name: CString,
+}
+
+impl DirEntry {
+    /// Returns the inode number.
+    #[inline]
+    pub fn ino(&self) -> u64 {
+        self.ino
+    }
+
+    /// Returns the file type reported by the filesystem, which can be
+    /// [FileType::Unknown].
+    #[inline]
+    pub fn file_type(&self) -> FileType {
+        self.file_type
+    }
+
+    /// Returns the name of the entry.
+    #[inline]
+    pub fn name(&self) -> &CStr {
+        &self.name
+    }
+
+    /// Returns the name of the entry, consuming it.
+    #[inline]
+    pub fn into_name(self) -> CString {
+        self.name
+    }
+}
+
+/// Parse the records returned by `getdents64`, returns [Errno::EIO] for
+/// records with an invalid length.
+fn parse(buf: &[u8], entries: &mut Vec<DirEntry>) -> Result<(), Errno> {
+    let mut off = 0;
+    while off < buf.len() {
+        let record = &buf[off..];
+        if record.len() < D_NAME + 1 {
+            return Err(Errno::EIO);
+        }
+        let reclen = u16::from_ne_bytes([record[D_RECLEN], record[D_RECLEN + 1]]) as usize;
+        if reclen < D_NAME + 1 || reclen > record.len() {
+            return Err(Errno::EIO);
+        }
+        let name = &record[D_NAME..reclen];
+        let name = &name[..name.iter().position(|&c| c == 0).unwrap_or(name.len())];
+        off += reclen;
+
+        if name == b"." || name == b".." {
+            continue;
+        }
+        let mut ino = [0u8; 8];
+        ino.copy_from_slice(&record[D_INO..D_INO + 8]);
+        entries.push(DirEntry {
+            ino: u64::from_ne_bytes(ino),
+            file_type: crate::raw::file_type((record[D_TYPE] as u16) << 12),
+            name: unsafe { CString::from_vec_unchecked(name.to_vec()) },
+        });
+    }
+    Ok(())
+}
+
+/// Returns the entries of the directory `path` relative to `dirfd`, without
+/// `.` and `..`, in the order returned by `getdents64`.
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+#[inline]
+pub unsafe fn list_dir<P: AsRef<Path>>(dirfd: RawFd, path: P) -> Result<Vec<DirEntry>, Errno> {
+    run_with_cstr(path, |path| list_dir_cstr(dirfd, path))
+}
+
+/// Returns the entries of the directory `path` relative to `dirfd`, without
+/// `.` and `..`. Accept `path` as a [CStr].
+///
+/// See [crate::list_dir].
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+pub unsafe fn list_dir_cstr(dirfd: RawFd, path: &CStr) -> Result<Vec<DirEntry>, Errno> {
+    let fd = sys::openat(dirfd, path, O_RDONLY | O_DIRECTORY | O_CLOEXEC, 0)?;
+    let mut buf = alloc::vec![0u8; BUF_SIZE];
+    let mut entries = Vec::new();
+    loop {
+        let len = crate::retry(|| {
+            syscall!(
+                Sysno::getdents64,
+                fd.as_raw_fd(),
+                buf.as_mut_ptr(),
+                buf.len()
+            )
+        })?;
+        if len == 0 {
+            return Ok(entries);
+        }
+        parse(&buf[..len], &mut entries)?;
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    fn record(reclen: u16, name: &[u8]) -> Vec<u8> {
+        let mut record = vec![0u8; D_NAME];
+        record[D_RECLEN..D_RECLEN + 2].copy_from_slice(&reclen.to_ne_bytes());
+        record.extend_from_slice(name);
+        record.resize((reclen as usize).max(record.len() + 1), 0);
+        record
+    }
+
+    #[test]
+    fn parse_records() {
+        let mut entries = Vec::new();
+        let mut buf = record(24, b"file");
+        buf.extend(record(24, b".."));
+        assert_eq!(parse(&buf, &mut entries), Ok(()));
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name().to_bytes(), b"file");
+
+        for buf in [
+            record(0, b"file"),
+            record(D_NAME as u16, b""),
+            record(24, b"file")[..23].to_vec(),
+            record(24, b"file")[..D_NAME].to_vec(),
+        ] {
+            assert_eq!(parse(&buf, &mut Vec::new()), Err(Errno::EIO));
+        }
+    }
+
+    #[test]
+    fn list() {
+        linux_syscalls::init();
+
+        let dir = crate::tests::TempDir::new("dir");
+        std::fs::create_dir(dir.join("sub")).unwrap();
+        for i in 0..1000 {
+            std::fs::write(dir.join(format!("file-{:04}", i)), b"").unwrap();
+        }
+
+        let mut entries =
+            crate::retry(|| unsafe { list_dir(crate::CURRENT_DIRECTORY, &dir) }).unwrap();
+        entries.sort_by(|a, b| a.name().cmp(b.name()));
+        assert_eq!(entries.len(), 1001);
+        assert_eq!(entries[0].name().to_bytes(), b"file-0000");
+        assert_eq!(entries[999].name().to_bytes(), b"file-0999");
+        assert_eq!(entries[1000].name().to_bytes(), b"sub");
+        let sub = crate::stat(dir.join("sub")).unwrap();
+        assert_eq!(entries[1000].ino(), sub.inode());
+        assert!(matches!(
+            entries[1000].file_type(),
+            FileType::Directory | FileType::Unknown
+        ));
+
+        assert_eq!(
+            unsafe { list_dir(crate::CURRENT_DIRECTORY, dir.join("file-0000")) },
+            Err(Errno::ENOTDIR)
+        );
+    }
+}
@@ -0,0 +1,250 @@
+#[cfg(feature = "alloc")]
+use linux_syscalls::Errno;
+
+#[cfg(feature = "alloc")]
+use crate::{run_with_cstr, Path, RawFd, Stat, StatAtFlags, CURRENT_DIRECTORY};
+
+#[cfg(feature = "std")]
+type PathOwned = std::path::PathBuf;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+type PathOwned = crate::CString;
+
+/// System call performed by [crate::fstatat].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StatOperation {
+    /// `statx()`, Linux 4.11 and later.
+    Statx,
+    /// `fstatat()` (`newfstatat()` or `fstatat64()`).
+    Fstatat,
+}
+
+impl StatOperation {
+    /// Returns the name of the system call.
+    #[inline]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Statx => "statx",
+            Self::Fstatat => "fstatat",
+        }
+    }
+}
+
+impl core::fmt::Display for StatOperation {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Cause of a [StatError].
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatErrorKind {
+    /// The system call failed.
+    Errno(Errno),
+    /// The path contains a NUL byte at the given position, no system call
+    /// was performed.
+    InteriorNul(usize),
+}
+
+/// Error returned by [crate::fstatat_with_context] with the operation, the
+/// path and the flags of the failed call.
+///
+/// With `std` it converts into [std::io::Error] keeping the [std::io::ErrorKind] of the
+/// errno, or [std::io::ErrorKind::InvalidInput] for
+/// [StatErrorKind::InteriorNul], with this error as payload.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatError {
+    kind: StatErrorKind,
+    operation: Option<StatOperation>,
+    fallback: bool,
+    path: PathOwned,
+    flags: StatAtFlags,
+}
+
+#[cfg(feature = "alloc")]
+impl StatError {
+    /// Returns the cause of the error.
+    #[inline]
+    pub fn kind(&self) -> StatErrorKind {
+        self.kind
+    }
+
+    /// Returns the [Errno] of the failed system call, `None` for
+    /// [StatErrorKind::InteriorNul].
+    #[inline]
+    pub fn errno(&self) -> Option<Errno> {
+        match self.kind {
+            StatErrorKind::Errno(errno) => Some(errno),
+            StatErrorKind::InteriorNul(_) => None,
+        }
+    }
+
+    /// Returns the failed system call, `None` if no system call was
+    /// performed.
+    #[inline]
+    pub fn operation(&self) -> Option<StatOperation> {
+        self.operation
+    }
+
+    /// Returns true if [StatOperation::Fstatat] was used because `statx` is
+    /// not available.
+    #[inline]
+    pub fn is_fallback(&self) -> bool {
+        self.fallback
+    }
+
+    /// Returns the path passed to the call.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the flags passed to the call.
+    #[inline]
+    pub fn flags(&self) -> StatAtFlags {
+        self.flags
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl core::fmt::Display for StatError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match (self.kind, self.operation) {
+            (StatErrorKind::Errno(errno), Some(operation)) => {
+                write!(f, "{}({:?}, {:?})", operation, self.path, self.flags)?;
+                if self.fallback {
+                    f.write_str(" (statx fallback)")?;
+                }
+                write!(f, ": {}", errno)
+            }
+            (StatErrorKind::Errno(errno), None) => write!(f, "{:?}: {}", self.path, errno),
+            (StatErrorKind::InteriorNul(pos), _) => {
+                write!(f, "path {:?} contains a NUL byte at {}", self.path, pos)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StatError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self.kind {
+            StatErrorKind::Errno(ref errno) => Some(errno),
+            StatErrorKind::InteriorNul(_) => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<StatError> for std::io::Error {
+    fn from(err: StatError) -> Self {
+        let kind = match err.kind {
+            StatErrorKind::Errno(errno) => std::io::Error::from(errno).kind(),
+            StatErrorKind::InteriorNul(_) => std::io::ErrorKind::InvalidInput,
+        };
+        Self::new(kind, err)
+    }
+}
+
+/// Like [crate::fstatat] but returns a [StatError] with the context of the
+/// failure.
+///
+/// With `std`, a path containing a NUL byte (other than a trailing one)
+/// fails with [StatErrorKind::InteriorNul] and its position, where the
+/// other functions of this crate return [Errno::EINVAL].
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+#[cfg(feature = "alloc")]
+pub unsafe fn fstatat_with_context<P: AsRef<Path>>(
+    dirfd: RawFd,
+    path: P,
+    flags: StatAtFlags,
+) -> Result<Stat, StatError> {
+    let path = path.as_ref();
+    let error = |kind, operation, fallback| StatError {
+        kind,
+        operation,
+        fallback,
+        path: PathOwned::from(path),
+        flags,
+    };
+
+    #[cfg(feature = "std")]
+    {
+        let bytes = std::os::unix::ffi::OsStrExt::as_bytes(path.as_os_str());
+        if let Some(pos) = bytes.iter().position(|&c| c == 0) {
+            if pos + 1 != bytes.len() {
+                return Err(error(StatErrorKind::InteriorNul(pos), None, false));
+            }
+        }
+    }
+
+    let mut trace = None;
+    run_with_cstr(path, |path| {
+        let (res, operation, fallback) =
+            crate::fstatat_traced(dirfd, path, flags, crate::RetryPolicy::global());
+        trace = Some((operation, fallback));
+        res
+    })
+    .map_err(|errno| {
+        let (operation, fallback) = trace.map_or((None, false), |(op, fb)| (Some(op), fb));
+        error(StatErrorKind::Errno(errno), operation, fallback)
+    })
+}
+
+/// Call [crate::fstatat_with_context] for `path` in the current directory
+/// following symlinks.
+#[cfg(feature = "alloc")]
+#[inline]
+pub fn stat_with_context<P: AsRef<Path>>(path: P) -> Result<Stat, StatError> {
+    unsafe { fstatat_with_context(CURRENT_DIRECTORY, path, StatAtFlags::empty()) }
+}
+
+/// Call [crate::fstatat_with_context] for `path` in the current directory
+/// not following symlinks.
+#[cfg(feature = "alloc")]
+#[inline]
+pub fn lstat_with_context<P: AsRef<Path>>(path: P) -> Result<Stat, StatError> {
+    unsafe { fstatat_with_context(CURRENT_DIRECTORY, path, StatAtFlags::SYMLINK_NOFOLLOW) }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::{retry, tests::dev_null};
+
+    #[test]
+    fn context() {
+        linux_syscalls::init();
+
+        assert!(
+            retry(|| stat_with_context(dev_null()).map_err(|err| err.errno().unwrap()))
+                .unwrap()
+                .is_char()
+        );
+
+        let path = Path::new("/linux-stat-missing");
+        let err = lstat_with_context(path).unwrap_err();
+        assert_eq!(err.errno(), Some(Errno::ENOENT));
+        assert_eq!(err.kind(), StatErrorKind::Errno(Errno::ENOENT));
+        assert!(err.operation().is_some());
+        assert_eq!(err.path(), path);
+        assert_eq!(err.flags(), StatAtFlags::SYMLINK_NOFOLLOW);
+        assert!(err.to_string().contains("\"/linux-stat-missing\""));
+        let io = std::io::Error::from(err);
+        assert_eq!(io.kind(), std::io::ErrorKind::NotFound);
+
+        let err = stat_with_context("/dev\0/null").unwrap_err();
+        assert_eq!(err.kind(), StatErrorKind::InteriorNul(4));
+        assert_eq!(err.errno(), None);
+        assert_eq!(err.operation(), None);
+        let io = std::io::Error::from(err);
+        assert_eq!(io.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(io.get_ref().unwrap().is::<StatError>());
+    }
+}
@@ -0,0 +1,254 @@
+use linux_syscalls::Errno;
+
+use crate::{fstat, run_with_cstr, stat_cstr, CStr, Dev, Path, RawFd, Stat};
+
+/// File identity, the device and inode pair of a file.
+///
+/// Two paths refer to the same file if they have the same [FileId]. Use
+/// [MountFileId] to also tell apart the same file reached through different
+/// mounts (e.g. bind mounts).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId {
+    dev: Dev,
+    inode: u64,
+}
+
+impl FileId {
+    /// Create a new [FileId] from device and inode.
+    #[inline]
+    pub const fn new(dev: Dev, inode: u64) -> Self {
+        Self { dev, inode }
+    }
+
+    /// Create a new [FileId] from a [Stat].
+    #[inline]
+    pub const fn from_stat(stat: &Stat) -> Self {
+        Self::new(stat.dev(), stat.inode())
+    }
+
+    /// Returns the device on which the file resides.
+    #[inline]
+    pub const fn dev(&self) -> Dev {
+        self.dev
+    }
+
+    /// Returns the inode number of the file.
+    #[inline]
+    pub const fn inode(&self) -> u64 {
+        self.inode
+    }
+}
+
+/// File identity including the mount ID, which tells apart the same file
+/// reached through different mounts (e.g. bind mounts).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MountFileId {
+    file_id: FileId,
+    mount_id: u64,
+}
+
+impl MountFileId {
+    /// Create a new [MountFileId] from device, inode and mount ID.
+    #[inline]
+    pub const fn new(dev: Dev, inode: u64, mount_id: u64) -> Self {
+        Self {
+            file_id: FileId::new(dev, inode),
+            mount_id,
+        }
+    }
+
+    /// Create a new [MountFileId] from a [Stat], `None` if the mount ID is
+    /// not available (it requires `statx` on Linux 5.8 or later).
+    #[inline]
+    pub const fn from_stat(stat: &Stat) -> Option<Self> {
+        match crate::mount_id(stat) {
+            Some(mount_id) => Some(Self::new(stat.dev(), stat.inode(), mount_id)),
+            None => None,
+        }
+    }
+
+    /// Returns the device and inode pair.
+    #[inline]
+    pub const fn file_id(&self) -> FileId {
+        self.file_id
+    }
+
+    /// Returns the mount ID.
+    #[inline]
+    pub const fn mount_id(&self) -> u64 {
+        self.mount_id
+    }
+}
+
+impl From<MountFileId> for FileId {
+    #[inline]
+    fn from(value: MountFileId) -> Self {
+        value.file_id
+    }
+}
+
+impl From<&Stat> for FileId {
+    #[inline]
+    fn from(value: &Stat) -> Self {
+        Self::from_stat(value)
+    }
+}
+
+impl From<Stat> for FileId {
+    #[inline]
+    fn from(value: Stat) -> Self {
+        Self::from_stat(&value)
+    }
+}
+
+/// Returns true if `a` and `b` refer to the same file, following symlinks.
+#[inline]
+pub fn same_file<P: AsRef<Path>, Q: AsRef<Path>>(a: P, b: Q) -> Result<bool, Errno> {
+    run_with_cstr(a, |a| run_with_cstr(b, |b| same_file_cstr(a, b)))
+}
+
+/// Returns true if `a` and `b` refer to the same file, following symlinks.
+/// Accept `a` and `b` as [CStr].
+#[inline]
+pub fn same_file_cstr(a: &CStr, b: &CStr) -> Result<bool, Errno> {
+    Ok(FileId::from_stat(&stat_cstr(a)?) == FileId::from_stat(&stat_cstr(b)?))
+}
+
+/// Returns true if the file descriptors `a` and `b` refer to the same file.
+///
+/// # Safety
+///
+/// This function is marked as unsafe because file descriptors (`a` and `b`)
+/// cannot be checked.
+#[inline]
+pub unsafe fn same_file_fd(a: RawFd, b: RawFd) -> Result<bool, Errno> {
+    Ok(FileId::from_stat(&fstat(a)?) == FileId::from_stat(&fstat(b)?))
+}
+
+/// Returns true if `a` and `b` reside on the same filesystem, following
+/// symlinks.
+#[inline]
+pub fn is_same_filesystem<P: AsRef<Path>, Q: AsRef<Path>>(a: P, b: Q) -> Result<bool, Errno> {
+    run_with_cstr(a, |a| run_with_cstr(b, |b| is_same_filesystem_cstr(a, b)))
+}
+
+/// Returns true if `a` and `b` reside on the same filesystem, following
+/// symlinks. Accept `a` and `b` as [CStr].
+#[inline]
+pub fn is_same_filesystem_cstr(a: &CStr, b: &CStr) -> Result<bool, Errno> {
+    Ok(stat_cstr(a)?.dev() == stat_cstr(b)?.dev())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dev_null_identity() {
+        linux_syscalls::init();
+
+        let dev_null = crate::tests::dev_null();
+        assert_eq!(crate::retry(|| same_file(dev_null, dev_null)), Ok(true));
+        assert_eq!(
+            crate::retry(|| is_same_filesystem(dev_null, dev_null)),
+            Ok(true)
+        );
+
+        let stat = crate::retry(|| crate::stat(dev_null)).unwrap();
+        let id = FileId::from_stat(&stat);
+        assert_eq!(id, FileId::new(stat.dev(), stat.inode()));
+
+        let with_mount = MountFileId::from_stat(&stat);
+        assert_eq!(with_mount.is_some(), crate::mount_id(&stat).is_some());
+        if let Some(with_mount) = with_mount {
+            assert_eq!(FileId::from(with_mount), id);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn distinct_mounts() {
+        linux_syscalls::init();
+
+        let root = crate::retry(|| crate::stat("/")).unwrap();
+        let proc = crate::retry(|| crate::stat("/proc")).unwrap();
+        match (MountFileId::from_stat(&root), MountFileId::from_stat(&proc)) {
+            (Some(root_id), Some(proc_id)) => {
+                assert_ne!(root_id.mount_id(), proc_id.mount_id());
+                assert_ne!(root_id, proc_id);
+                assert_eq!(root_id.file_id(), FileId::from_stat(&root));
+                assert_eq!(proc_id.file_id(), FileId::from_stat(&proc));
+            }
+            _ => eprintln!("distinct_mounts skipped: no mount ID before Linux 5.8"),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    #[ignore = "needs CAP_SYS_ADMIN to create a mount namespace"]
+    fn bind_mount() {
+        linux_syscalls::init();
+
+        let dir = crate::tests::TempDir::new("bind");
+        std::fs::create_dir(dir.join("src")).unwrap();
+        std::fs::create_dir(dir.join("dst")).unwrap();
+
+        // The mount namespace of the thread is dropped when it exits.
+        let (src, dst) = (dir.join("src"), dir.join("dst"));
+        let ids = std::thread::spawn(move || unsafe {
+            use std::os::unix::ffi::OsStrExt;
+
+            let cstr =
+                |path: &std::path::Path| crate::CString::new(path.as_os_str().as_bytes()).unwrap();
+            let (src, dst) = (cstr(&src), cstr(&dst));
+            assert_eq!(
+                libc::unshare(libc::CLONE_NEWNS),
+                0,
+                "unshare: {}",
+                std::io::Error::last_os_error()
+            );
+            assert_eq!(
+                libc::mount(
+                    core::ptr::null(),
+                    b"/\0".as_ptr() as *const _,
+                    core::ptr::null(),
+                    libc::MS_REC | libc::MS_PRIVATE,
+                    core::ptr::null(),
+                ),
+                0,
+                "mount: {}",
+                std::io::Error::last_os_error()
+            );
+            assert_eq!(
+                libc::mount(
+                    src.as_ptr(),
+                    dst.as_ptr(),
+                    core::ptr::null(),
+                    libc::MS_BIND,
+                    core::ptr::null(),
+                ),
+                0,
+                "mount: {}",
+                std::io::Error::last_os_error()
+            );
+            let src = crate::retry(|| crate::stat_cstr(&src)).unwrap();
+            let dst = crate::retry(|| crate::stat_cstr(&dst)).unwrap();
+            (
+                (FileId::from_stat(&src), MountFileId::from_stat(&src)),
+                (FileId::from_stat(&dst), MountFileId::from_stat(&dst)),
+            )
+        })
+        .join()
+        .unwrap();
+
+        let ((src, src_mount), (dst, dst_mount)) = ids;
+        assert_eq!(src, dst);
+        match (src_mount, dst_mount) {
+            (Some(src_mount), Some(dst_mount)) => {
+                assert_eq!(src_mount.file_id(), dst_mount.file_id());
+                assert_ne!(src_mount, dst_mount);
+            }
+            _ => eprintln!("bind_mount: no mount ID before Linux 5.8, only FileId checked"),
+        }
+    }
+}
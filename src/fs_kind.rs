@@ -0,0 +1,382 @@
+use linux_syscalls::Errno;
+
+use crate::{CStr, Path, RawFd};
+
+macro_rules! filesystem_kinds {
+    ($($(#[$meta:meta])* $variant:ident = $name:literal, [$($magic:ident),+];)*) => {
+        /// Filesystem type identified by the `f_type` magic number returned by
+        /// [crate::statfs], from the kernel's `include/uapi/linux/magic.h`.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub enum FilesystemKind {
+            $($(#[$meta])* $variant,)*
+            /// Magic number not in this list.
+            Unknown(u64),
+        }
+
+        impl FilesystemKind {
+            /// Returns the filesystem type identified by `magic`.
+            pub const fn from_magic(magic: u64) -> Self {
+                if magic > u32::MAX as u64 {
+                    return Self::Unknown(magic);
+                }
+                match magic as u32 {
+                    $($(linux_raw_sys::general::$magic)|+ => Self::$variant,)*
+                    _ => Self::Unknown(magic),
+                }
+            }
+
+            /// Returns the magic number (the first one for filesystems with
+            /// more than one).
+            pub const fn magic(&self) -> u64 {
+                match self {
+                    $(Self::$variant => filesystem_kinds!(@first $($magic),+) as u64,)*
+                    Self::Unknown(magic) => *magic,
+                }
+            }
+
+            /// Returns the name of the filesystem type, `"unknown"` for
+            /// [FilesystemKind::Unknown].
+            pub const fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)*
+                    Self::Unknown(_) => "unknown",
+                }
+            }
+        }
+    };
+    (@first $first:ident $(, $rest:ident)*) => {
+        linux_raw_sys::general::$first
+    };
+}
+
+filesystem_kinds! {
+    /// Acorn Disc Filing System.
+    Adfs = "adfs", [ADFS_SUPER_MAGIC];
+    /// Amiga Fast File System.
+    Affs = "affs", [AFFS_SUPER_MAGIC];
+    /// Andrew File System.
+    Afs = "afs", [AFS_SUPER_MAGIC, AFS_FS_MAGIC];
+    /// AppArmor securityfs.
+    Apparmorfs = "apparmorfs", [AAFS_MAGIC];
+    /// Anonymous inodes (eventfd, epoll, ...).
+    AnonInodeFs = "anon_inodefs", [ANON_INODE_FS_MAGIC];
+    /// Automounter.
+    Autofs = "autofs", [AUTOFS_SUPER_MAGIC];
+    /// Block devices.
+    Bdevfs = "bdev", [BDEVFS_MAGIC];
+    /// Android binder.
+    Binderfs = "binder", [BINDERFS_SUPER_MAGIC];
+    /// Miscellaneous binary formats.
+    Binfmtfs = "binfmt_misc", [BINFMTFS_MAGIC];
+    /// BPF objects.
+    Bpffs = "bpf", [BPF_FS_MAGIC];
+    /// B-tree filesystem.
+    Btrfs = "btrfs", [BTRFS_SUPER_MAGIC];
+    /// Btrfs self-test filesystem.
+    BtrfsTest = "btrfs_test_fs", [BTRFS_TEST_MAGIC];
+    /// Ceph distributed filesystem.
+    Ceph = "ceph", [CEPH_SUPER_MAGIC];
+    /// Control groups version 1.
+    Cgroup = "cgroup", [CGROUP_SUPER_MAGIC];
+    /// Control groups version 2.
+    Cgroup2 = "cgroup2", [CGROUP2_SUPER_MAGIC];
+    /// Common Internet File System.
+    Cifs = "cifs", [CIFS_SUPER_MAGIC];
+    /// Coda distributed filesystem.
+    Coda = "coda", [CODA_SUPER_MAGIC];
+    /// Compressed ROM filesystem.
+    Cramfs = "cramfs", [CRAMFS_MAGIC, CRAMFS_MAGIC_WEND];
+    /// Direct access devices.
+    Daxfs = "dax", [DAXFS_MAGIC];
+    /// Debugging filesystem.
+    Debugfs = "debugfs", [DEBUGFS_MAGIC];
+    /// Unix98 pseudo-terminals.
+    Devpts = "devpts", [DEVPTS_SUPER_MAGIC];
+    /// Memory devices.
+    Devmem = "devmem", [DEVMEM_MAGIC];
+    /// DMA buffers.
+    DmaBuf = "dmabuf", [DMA_BUF_MAGIC];
+    /// eCryptfs stacked cryptographic filesystem.
+    Ecryptfs = "ecryptfs", [ECRYPTFS_SUPER_MAGIC];
+    /// EFI variables.
+    Efivarfs = "efivarfs", [EFIVARFS_MAGIC];
+    /// SGI Extent File System.
+    Efs = "efs", [EFS_SUPER_MAGIC];
+    /// Enhanced read-only filesystem.
+    Erofs = "erofs", [EROFS_SUPER_MAGIC_V1];
+    /// Extended Allocation Table.
+    Exfat = "exfat", [EXFAT_SUPER_MAGIC];
+    /// Second, third and fourth extended filesystems, which share the magic
+    /// number. The name is `ext4`, whose driver mounts all of them.
+    Ext = "ext4", [EXT4_SUPER_MAGIC];
+    /// Flash-Friendly File System.
+    F2fs = "f2fs", [F2FS_SUPER_MAGIC];
+    /// Filesystem in userspace.
+    Fuse = "fuse", [FUSE_SUPER_MAGIC];
+    /// Futexes.
+    Futexfs = "futexfs", [FUTEXFS_SUPER_MAGIC];
+    /// User-mode Linux host filesystem.
+    Hostfs = "hostfs", [HOSTFS_SUPER_MAGIC];
+    /// OS/2 High Performance File System.
+    Hpfs = "hpfs", [HPFS_SUPER_MAGIC];
+    /// Huge pages.
+    Hugetlbfs = "hugetlbfs", [HUGETLBFS_MAGIC];
+    /// ISO 9660 CD-ROM filesystem.
+    Isofs = "iso9660", [ISOFS_SUPER_MAGIC];
+    /// Journalling Flash File System version 2.
+    Jffs2 = "jffs2", [JFFS2_SUPER_MAGIC];
+    /// MINIX filesystem.
+    Minix = "minix", [
+        MINIX_SUPER_MAGIC,
+        MINIX_SUPER_MAGIC2,
+        MINIX2_SUPER_MAGIC,
+        MINIX2_SUPER_MAGIC2,
+        MINIX3_SUPER_MAGIC
+    ];
+    /// FAT filesystem.
+    Msdos = "msdos", [MSDOS_SUPER_MAGIC];
+    /// MTD inodes.
+    MtdInodeFs = "mtd_inodefs", [MTD_INODE_FS_MAGIC];
+    /// NetWare Core Protocol.
+    Ncp = "ncpfs", [NCP_SUPER_MAGIC];
+    /// Network File System.
+    Nfs = "nfs", [NFS_SUPER_MAGIC];
+    /// New Implementation of a Log-structured File System.
+    Nilfs = "nilfs2", [NILFS_SUPER_MAGIC];
+    /// Namespaces.
+    Nsfs = "nsfs", [NSFS_MAGIC];
+    /// Oracle Cluster File System 2.
+    Ocfs2 = "ocfs2", [OCFS2_SUPER_MAGIC];
+    /// OpenPROM device tree.
+    Openprom = "openpromfs", [OPENPROM_SUPER_MAGIC];
+    /// Overlay filesystem.
+    Overlayfs = "overlay", [OVERLAYFS_SUPER_MAGIC];
+    /// Pipes.
+    Pipefs = "pipefs", [PIPEFS_MAGIC];
+    /// Process information.
+    Proc = "proc", [PROC_SUPER_MAGIC];
+    /// Persistent storage for kernel logs.
+    Pstorefs = "pstore", [PSTOREFS_MAGIC];
+    /// QNX4 filesystem.
+    Qnx4 = "qnx4", [QNX4_SUPER_MAGIC];
+    /// QNX6 filesystem.
+    Qnx6 = "qnx6", [QNX6_SUPER_MAGIC];
+    /// RAM filesystem.
+    Ramfs = "ramfs", [RAMFS_MAGIC];
+    /// Resource control (Intel RDT).
+    Rdtgroup = "resctrl", [RDTGROUP_SUPER_MAGIC];
+    /// Reiser filesystem.
+    Reiserfs = "reiserfs", [REISERFS_SUPER_MAGIC];
+    /// Secret memory areas.
+    Secretmem = "secretmem", [SECRETMEM_MAGIC];
+    /// Security modules.
+    Securityfs = "securityfs", [SECURITYFS_MAGIC];
+    /// SELinux.
+    Selinuxfs = "selinuxfs", [SELINUX_MAGIC];
+    /// SMACK.
+    Smackfs = "smackfs", [SMACK_MAGIC];
+    /// SMB filesystem.
+    Smb = "smbfs", [SMB_SUPER_MAGIC];
+    /// SMB2/SMB3 filesystem.
+    Smb2 = "smb3", [SMB2_SUPER_MAGIC];
+    /// Sockets.
+    Sockfs = "sockfs", [SOCKFS_MAGIC];
+    /// Compressed read-only filesystem.
+    Squashfs = "squashfs", [SQUASHFS_MAGIC];
+    /// Kernel objects.
+    Sysfs = "sysfs", [SYSFS_MAGIC];
+    /// Temporary filesystem in virtual memory (also `devtmpfs`).
+    Tmpfs = "tmpfs", [TMPFS_MAGIC];
+    /// Tracing.
+    Tracefs = "tracefs", [TRACEFS_MAGIC];
+    /// Universal Disk Format.
+    Udf = "udf", [UDF_SUPER_MAGIC];
+    /// USB devices.
+    Usbdevfs = "usbdevfs", [USBDEVICE_SUPER_MAGIC];
+    /// Plan 9 resource sharing protocol.
+    V9fs = "9p", [V9FS_MAGIC];
+    /// Xen.
+    Xenfs = "xenfs", [XENFS_SUPER_MAGIC];
+    /// XFS filesystem.
+    Xfs = "xfs", [XFS_SUPER_MAGIC];
+    /// Zoned block devices.
+    Zonefs = "zonefs", [ZONEFS_MAGIC];
+}
+
+impl FilesystemKind {
+    /// Returns true if the filesystem is accessed over the network (e.g.
+    /// NFS, SMB, Ceph). FUSE filesystems are not considered network
+    /// filesystems even if some of them are (e.g. sshfs).
+    pub const fn is_network(&self) -> bool {
+        matches!(
+            self,
+            Self::Afs
+                | Self::Ceph
+                | Self::Cifs
+                | Self::Coda
+                | Self::Ncp
+                | Self::Nfs
+                | Self::Ocfs2
+                | Self::Smb
+                | Self::Smb2
+                | Self::V9fs
+        )
+    }
+
+    /// Returns true if the filesystem is a kernel interface without storage
+    /// for regular files (e.g. proc, sysfs, cgroup). Memory-backed
+    /// filesystems holding regular files (tmpfs, ramfs, hugetlbfs) are not
+    /// pseudo filesystems.
+    pub const fn is_pseudo(&self) -> bool {
+        matches!(
+            self,
+            Self::AnonInodeFs
+                | Self::Apparmorfs
+                | Self::Autofs
+                | Self::Bdevfs
+                | Self::Binderfs
+                | Self::Binfmtfs
+                | Self::Bpffs
+                | Self::Cgroup
+                | Self::Cgroup2
+                | Self::Debugfs
+                | Self::Devmem
+                | Self::Devpts
+                | Self::DmaBuf
+                | Self::Efivarfs
+                | Self::Futexfs
+                | Self::MtdInodeFs
+                | Self::Nsfs
+                | Self::Openprom
+                | Self::Pipefs
+                | Self::Proc
+                | Self::Pstorefs
+                | Self::Rdtgroup
+                | Self::Secretmem
+                | Self::Securityfs
+                | Self::Selinuxfs
+                | Self::Smackfs
+                | Self::Sockfs
+                | Self::Sysfs
+                | Self::Tracefs
+                | Self::Usbdevfs
+                | Self::Xenfs
+        )
+    }
+
+    /// Returns true if the filesystem usually records the creation time
+    /// (birth time) of files.
+    ///
+    /// This is a heuristic: ext2 and ext3, ext4 with 128 bytes inodes or
+    /// f2fs without the `inode_crtime` feature do not. Check
+    /// [crate::raw::StatXMask::BTIME] in the mask returned by
+    /// [crate::raw::statx] for a definitive answer.
+    pub const fn supports_btime(&self) -> bool {
+        matches!(
+            self,
+            Self::Btrfs | Self::Ceph | Self::Cifs | Self::Ext | Self::F2fs | Self::Smb2 | Self::Xfs
+        )
+    }
+}
+
+impl core::fmt::Display for FilesystemKind {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl crate::raw::statfs64 {
+    /// Returns the type of filesystem, see [FilesystemKind::from_magic].
+    #[inline]
+    pub const fn kind(&self) -> FilesystemKind {
+        FilesystemKind::from_magic(self.fs_type())
+    }
+}
+
+/// Returns the type of the filesystem containing `path`.
+#[inline]
+pub fn filesystem_kind<P: AsRef<Path>>(path: P) -> Result<FilesystemKind, Errno> {
+    crate::statfs(path).map(|statfs| statfs.kind())
+}
+
+/// Returns the type of the filesystem containing `path`.
+/// Accept `path` as a [CStr].
+#[inline]
+pub fn filesystem_kind_cstr(path: &CStr) -> Result<FilesystemKind, Errno> {
+    crate::statfs_cstr(path).map(|statfs| statfs.kind())
+}
+
+/// Returns the type of the filesystem containing the open file `fd`.
+///
+/// # Safety
+///
+/// This function is marked as unsafe because file descriptor (`fd`) cannot
+/// be checked.
+#[inline]
+pub unsafe fn filesystem_kind_fd(fd: RawFd) -> Result<FilesystemKind, Errno> {
+    crate::fstatfs(fd).map(|statfs| statfs.kind())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_magic() {
+        use linux_raw_sys::general::*;
+
+        assert_eq!(
+            FilesystemKind::from_magic(EXT2_SUPER_MAGIC as u64),
+            FilesystemKind::Ext
+        );
+        assert_eq!(
+            FilesystemKind::from_magic(MINIX2_SUPER_MAGIC2 as u64),
+            FilesystemKind::Minix
+        );
+        assert_eq!(
+            FilesystemKind::from_magic(CGROUP2_SUPER_MAGIC as u64).name(),
+            "cgroup2"
+        );
+        assert_eq!(
+            FilesystemKind::from_magic(0x1234),
+            FilesystemKind::Unknown(0x1234)
+        );
+        assert_eq!(
+            FilesystemKind::from_magic(1 << 40),
+            FilesystemKind::Unknown(1 << 40)
+        );
+        for kind in [
+            FilesystemKind::Tmpfs,
+            FilesystemKind::Btrfs,
+            FilesystemKind::Overlayfs,
+            FilesystemKind::Cramfs,
+            FilesystemKind::Unknown(42),
+        ] {
+            assert_eq!(FilesystemKind::from_magic(kind.magic()), kind);
+        }
+
+        assert!(FilesystemKind::Nfs.is_network());
+        assert!(!FilesystemKind::Fuse.is_network());
+        assert!(FilesystemKind::Sysfs.is_pseudo());
+        assert!(!FilesystemKind::Tmpfs.is_pseudo());
+        assert!(!FilesystemKind::Hugetlbfs.is_pseudo());
+        assert_eq!(FilesystemKind::Ext.name(), "ext4");
+        assert!(FilesystemKind::Xfs.supports_btime());
+        assert!(!FilesystemKind::Proc.supports_btime());
+    }
+
+    #[test]
+    fn proc_kind() {
+        linux_syscalls::init();
+
+        let proc = unsafe { CStr::from_bytes_with_nul_unchecked(b"/proc/self\0") };
+        let kind = crate::retry(|| filesystem_kind_cstr(proc)).unwrap();
+        assert_eq!(kind, FilesystemKind::Proc);
+        assert!(kind.is_pseudo());
+        assert_eq!(
+            unsafe { filesystem_kind_fd(-1) }.map(|_| ()),
+            Err(Errno::EBADF)
+        );
+    }
+}
@@ -0,0 +1,270 @@
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use linux_raw_sys::general::{O_CLOEXEC, O_CREAT, O_DIRECTORY, O_EXCL, O_RDONLY, O_WRONLY};
+use linux_syscalls::Errno;
+
+use crate::{fstat, run_with_cstr, sys, CStr, Path, RawFd, Timestamp, CURRENT_DIRECTORY};
+
+/// Resolution of the timestamps stored by a filesystem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TimestampGranularity {
+    /// Timestamps are stored with nanosecond resolution (ext4, xfs, btrfs,
+    /// tmpfs, ...).
+    Nanoseconds,
+    /// Timestamps are stored with microsecond resolution (NFSv3, some FUSE
+    /// filesystems, ...).
+    Microseconds,
+    /// Timestamps are stored as whole seconds (ext3, some FUSE filesystems,
+    /// ...).
+    Seconds,
+    /// Timestamps are stored with any other resolution, expressed in
+    /// nanoseconds (e.g. 100ns on NTFS, 2s on FAT).
+    Other(u64),
+}
+
+impl TimestampGranularity {
+    /// Returns the resolution in nanoseconds.
+    #[inline]
+    pub const fn as_nanos(&self) -> u64 {
+        match *self {
+            Self::Nanoseconds => 1,
+            Self::Microseconds => 1_000,
+            Self::Seconds => 1_000_000_000,
+            Self::Other(nanos) => nanos,
+        }
+    }
+
+    /// Returns the [TimestampGranularity] for a resolution in nanoseconds.
+    #[inline]
+    pub const fn from_nanos(nanos: u64) -> Self {
+        match nanos {
+            1 => Self::Nanoseconds,
+            1_000 => Self::Microseconds,
+            1_000_000_000 => Self::Seconds,
+            nanos => Self::Other(nanos),
+        }
+    }
+
+    /// Returns true if two timestamps are indistinguishable with this
+    /// resolution.
+    #[inline]
+    pub const fn same(&self, a: Timestamp, b: Timestamp) -> bool {
+        let nanos = self.as_nanos() as i128;
+        let a = a.secs as i128 * 1_000_000_000 + a.nsecs as i128;
+        let b = b.secs as i128 * 1_000_000_000 + b.nsecs as i128;
+        a.div_euclid(nanos) == b.div_euclid(nanos)
+    }
+}
+
+/// An odd number of seconds and a nanoseconds part with every digit set, so
+/// that any truncation done by the filesystem is detectable.
+const PROBE: Timestamp = Timestamp::new(1_000_000_001, 123_456_789);
+
+fn granularity_of(stored: Timestamp) -> Result<TimestampGranularity, Errno> {
+    if stored.secs == PROBE.secs {
+        if stored.nsecs == 0 {
+            return Ok(TimestampGranularity::Seconds);
+        }
+
+        let mut nanos = 1u64;
+        while nanos < 1_000_000_000 && stored.nsecs as u64 % (nanos * 10) == 0 {
+            nanos *= 10;
+        }
+        Ok(TimestampGranularity::from_nanos(nanos))
+    } else if stored.secs == PROBE.secs - 1 && stored.nsecs == 0 {
+        // Rounded down to an even number of seconds.
+        Ok(TimestampGranularity::Other(2_000_000_000))
+    } else {
+        Err(Errno::EIO)
+    }
+}
+
+/// Writes the scratch file name into `buf` and returns it as a [CStr].
+fn scratch_name(buf: &mut [u8; 48]) -> &CStr {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+    const PREFIX: &[u8] = b".linux-stat-probe-";
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+
+    fn push_hex(buf: &mut [u8], mut len: usize, value: u32) -> usize {
+        for i in (0..8).rev() {
+            buf[len] = HEX[((value >> (i * 4)) & 0xf) as usize];
+            len += 1;
+        }
+        len
+    }
+
+    buf[..PREFIX.len()].copy_from_slice(PREFIX);
+    let mut len = push_hex(buf, PREFIX.len(), sys::getpid());
+    buf[len] = b'-';
+    len = push_hex(buf, len + 1, COUNTER.fetch_add(1, Ordering::Relaxed));
+    buf[len] = 0;
+
+    unsafe { CStr::from_bytes_with_nul_unchecked(&buf[..=len]) }
+}
+
+unsafe fn probe_dirfd(dirfd: RawFd) -> Result<TimestampGranularity, Errno> {
+    let mut buf = [0u8; 48];
+    let (name, file) = loop {
+        let name = scratch_name(&mut buf);
+        match sys::openat(dirfd, name, O_WRONLY | O_CREAT | O_EXCL | O_CLOEXEC, 0o600) {
+            Err(Errno::EEXIST) => (),
+            Err(err) => return Err(err),
+            Ok(fd) => break (name, fd),
+        }
+    };
+
+    let res = crate::futimens(file.as_raw_fd(), Some(PROBE), Some(PROBE))
+        .and_then(|_| fstat(file.as_raw_fd()))
+        .and_then(|stat| granularity_of(stat.mtime()));
+    drop(file);
+    let _ = sys::unlinkat(dirfd, name, 0);
+    res
+}
+
+/// Probe the timestamp resolution of the filesystem containing the
+/// directory `path` relative to `dirfd`.
+///
+/// A scratch file is created in the directory, its timestamps are set to a
+/// known value and read back, then it is removed.
+/// The result is not cached, see [crate::timestamp_granularity].
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+#[inline]
+pub unsafe fn probe_timestamp_granularity_at<P: AsRef<Path>>(
+    dirfd: RawFd,
+    path: P,
+) -> Result<TimestampGranularity, Errno> {
+    run_with_cstr(path, |path| {
+        probe_timestamp_granularity_at_cstr(dirfd, path)
+    })
+}
+
+/// Probe the timestamp resolution of the filesystem containing the
+/// directory `path` relative to `dirfd`. Accept `path` as a [CStr].
+///
+/// A scratch file is created in the directory, its timestamps are set to a
+/// known value and read back, then it is removed.
+/// The result is not cached, see [crate::timestamp_granularity].
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+pub unsafe fn probe_timestamp_granularity_at_cstr(
+    dirfd: RawFd,
+    path: &CStr,
+) -> Result<TimestampGranularity, Errno> {
+    let dir = sys::openat(dirfd, path, O_RDONLY | O_DIRECTORY | O_CLOEXEC, 0)?;
+    probe_dirfd(dir.as_raw_fd())
+}
+
+/// Call [crate::probe_timestamp_granularity_at] for the directory `path` in
+/// the current directory.
+#[inline]
+pub fn probe_timestamp_granularity<P: AsRef<Path>>(path: P) -> Result<TimestampGranularity, Errno> {
+    run_with_cstr(path, probe_timestamp_granularity_cstr)
+}
+
+/// Call [crate::probe_timestamp_granularity_at] for the directory `path` in
+/// the current directory. Accept `path` as a [CStr].
+#[inline]
+pub fn probe_timestamp_granularity_cstr(path: &CStr) -> Result<TimestampGranularity, Errno> {
+    unsafe { probe_timestamp_granularity_at_cstr(CURRENT_DIRECTORY, path) }
+}
+
+/// Granularity of the filesystems already probed, by device and mount ID.
+#[cfg(feature = "std")]
+#[allow(clippy::type_complexity)]
+static CACHE: std::sync::Mutex<Vec<((crate::Dev, Option<u64>), TimestampGranularity)>> =
+    std::sync::Mutex::new(Vec::new());
+
+/// Returns the timestamp resolution of the filesystem containing the
+/// directory `path`.
+///
+/// Like [crate::probe_timestamp_granularity] but the result is cached per
+/// device and mount ID, so the filesystem is probed only once (concurrent
+/// first calls may each probe it, but only one result is kept).
+#[cfg(feature = "std")]
+pub fn timestamp_granularity<P: AsRef<Path>>(path: P) -> Result<TimestampGranularity, Errno> {
+    run_with_cstr(path, |path| unsafe {
+        let dir = sys::openat(
+            CURRENT_DIRECTORY,
+            path,
+            O_RDONLY | O_DIRECTORY | O_CLOEXEC,
+            0,
+        )?;
+        let stat = fstat(dir.as_raw_fd())?;
+        let key = (stat.dev(), crate::mount_id(&stat));
+
+        let cache = CACHE.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some((_, granularity)) = cache.iter().find(|(k, _)| *k == key) {
+            return Ok(*granularity);
+        }
+        drop(cache);
+
+        let granularity = probe_dirfd(dir.as_raw_fd())?;
+        // Another thread may have probed the same filesystem meanwhile.
+        let mut cache = CACHE.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some((_, granularity)) = cache.iter().find(|(k, _)| *k == key) {
+            return Ok(*granularity);
+        }
+        cache.push((key, granularity));
+        Ok(granularity)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn granularity_from_stored_timestamp() {
+        assert_eq!(granularity_of(PROBE), Ok(TimestampGranularity::Nanoseconds));
+        assert_eq!(
+            granularity_of(Timestamp::new(PROBE.secs, 123_456_000)),
+            Ok(TimestampGranularity::Microseconds)
+        );
+        assert_eq!(
+            granularity_of(Timestamp::new(PROBE.secs, 123_456_700)),
+            Ok(TimestampGranularity::Other(100))
+        );
+        assert_eq!(
+            granularity_of(Timestamp::new(PROBE.secs, 0)),
+            Ok(TimestampGranularity::Seconds)
+        );
+        assert_eq!(
+            granularity_of(Timestamp::new(PROBE.secs - 1, 0)),
+            Ok(TimestampGranularity::Other(2_000_000_000))
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn probe_temp_dir() {
+        linux_syscalls::init();
+
+        let dir = crate::tests::TempDir::new("granularity");
+        let probed = crate::retry(|| probe_timestamp_granularity(&dir)).unwrap();
+        let cached = crate::retry(|| timestamp_granularity(&dir)).unwrap();
+        assert_eq!(probed, cached);
+        assert_eq!(timestamp_granularity(&dir), Ok(cached));
+
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let dir = dir.to_path_buf();
+                std::thread::spawn(move || crate::retry(|| timestamp_granularity(&dir)))
+            })
+            .collect();
+        for thread in threads {
+            assert_eq!(thread.join().unwrap(), Ok(cached));
+        }
+        let stat = crate::stat(&dir).unwrap();
+        let key = (stat.dev(), crate::mount_id(&stat));
+        let cache = CACHE.lock().unwrap_or_else(|err| err.into_inner());
+        assert_eq!(cache.iter().filter(|(k, _)| *k == key).count(), 1);
+    }
+}
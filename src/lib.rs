@@ -11,11 +11,19 @@ pub type RawFd = cty::c_int;
 /// Raw file descriptor.
 pub type RawFd = core::ffi::c_int;
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 #[cfg(not(extern_cstr))]
 pub use core::ffi::CStr;
 #[cfg(extern_cstr)]
 pub use cstr_core::CStr;
 
+#[cfg(all(feature = "alloc", not(extern_cstr)))]
+pub(crate) use alloc::ffi::CString;
+#[cfg(all(feature = "alloc", extern_cstr))]
+pub(crate) use cstr_core::CString;
+
 #[cfg(feature = "std")]
 pub use std::path::Path;
 #[cfg(not(feature = "std"))]
@@ -23,14 +31,71 @@ pub use CStr as Path;
 
 pub use linux_syscalls::Errno;
 
+mod access;
+#[cfg(feature = "std")]
+mod block;
+mod chmod;
+mod credentials;
 mod dev;
+#[cfg(feature = "std")]
+mod dev_node;
+mod device_kind;
+#[cfg(feature = "alloc")]
+mod dir;
+mod error;
+mod file_id;
+mod fs_kind;
+mod granularity;
+mod long_path;
+mod mode;
+#[cfg(feature = "std")]
+mod proc_devices;
 pub mod raw;
+mod resolve;
+mod retry;
+#[cfg(feature = "std")]
+mod secure_path;
+mod signature;
+mod stack_path;
+mod statfs;
+mod sys;
+mod terminal;
+mod utime;
+mod verified;
 
 use core::fmt;
 
 use linux_syscalls::bitflags;
 
+pub use self::access::*;
+#[cfg(feature = "std")]
+pub use self::block::*;
+pub use self::chmod::*;
+pub use self::credentials::*;
 pub use self::dev::*;
+#[cfg(feature = "std")]
+pub use self::dev_node::*;
+pub use self::device_kind::*;
+#[cfg(feature = "alloc")]
+pub use self::dir::*;
+pub use self::error::*;
+pub use self::file_id::*;
+pub use self::fs_kind::*;
+pub use self::granularity::*;
+pub use self::long_path::*;
+pub use self::mode::*;
+#[cfg(feature = "std")]
+pub use self::proc_devices::*;
+pub use self::resolve::*;
+pub use self::retry::*;
+#[cfg(feature = "std")]
+pub use self::secure_path::*;
+pub use self::signature::*;
+pub use self::stack_path::*;
+pub use self::statfs::*;
+pub use self::terminal::*;
+pub use self::utime::*;
+pub use self::verified::*;
 
 /// Special file descriptor that represent the current directory.
 pub const CURRENT_DIRECTORY: RawFd = linux_raw_sys::general::AT_FDCWD;
@@ -139,34 +204,9 @@ impl From<u16> for Mode {
 }
 
 impl fmt::Debug for Mode {
+    #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fn format_c(f: &mut fmt::Formatter<'_>, b: bool, c: char) -> fmt::Result {
-            if b {
-                fmt::Display::fmt(&c, f)
-            } else {
-                fmt::Display::fmt(&'-', f)
-            }
-        }
-
-        fn format_perm(
-            f: &mut fmt::Formatter<'_>,
-            p: ModePermission,
-            x: Option<char>,
-        ) -> fmt::Result {
-            format_c(f, p.contains(ModePermission::READ), 'r')?;
-            format_c(f, p.contains(ModePermission::WRITE), 'w')?;
-            if let Some(x) = x {
-                fmt::Display::fmt(&x, f)
-            } else {
-                format_c(f, p.contains(ModePermission::EXEC), 'x')
-            }
-        }
-
-        write!(f, "Mode(")?;
-        format_perm(f, self.owner(), if self.suid() { Some('s') } else { None })?;
-        format_perm(f, self.group(), if self.sgid() { Some('s') } else { None })?;
-        format_perm(f, self.other(), None)?;
-        write!(f, ")")
+        write!(f, "Mode({})", self)
     }
 }
 
@@ -178,6 +218,12 @@ pub struct Timestamp {
 }
 
 impl Timestamp {
+    /// Create a new [Timestamp] from seconds and nanoseconds.
+    #[inline]
+    pub const fn new(secs: i64, nsecs: u32) -> Self {
+        Self { secs, nsecs }
+    }
+
     /// Returns the seconds part.
     #[inline]
     pub const fn seconds(&self) -> i64 {
@@ -250,7 +296,7 @@ impl FileType {
 }
 
 #[cfg(all(not(feature = "linux_4_11"), not(target_arch = "loongarch64")))]
-static mut HAS_STATX: core::sync::atomic::AtomicU8 = core::sync::atomic::AtomicU8::new(2);
+static HAS_STATX: core::sync::atomic::AtomicU8 = core::sync::atomic::AtomicU8::new(2);
 
 /// Unified Stat structure.
 #[cfg(any(feature = "linux_4_11", target_arch = "loongarch64"))]
@@ -454,6 +500,24 @@ impl fmt::Debug for Stat {
     }
 }
 
+/// Returns the mount ID of `stat` if the kernel filled it.
+#[inline]
+pub(crate) const fn mount_id(stat: &Stat) -> Option<u64> {
+    #[cfg(all(not(feature = "linux_4_11"), not(target_arch = "loongarch64")))]
+    let statx = match stat {
+        Stat::Statx(statx) => statx,
+        Stat::Stat64(_) => return None,
+    };
+    #[cfg(any(feature = "linux_4_11", target_arch = "loongarch64"))]
+    let statx = stat;
+
+    if statx.mask().contains(crate::raw::StatXMask::MNT_ID) {
+        Some(statx.mount_id())
+    } else {
+        None
+    }
+}
+
 /// Returns an empty path representation.
 #[inline]
 pub fn empty_path() -> &'static Path {
@@ -464,6 +528,9 @@ pub fn empty_path() -> &'static Path {
     empty
 }
 
+/// Call `f` with `path` as a NUL terminated [CStr], copied on the stack if
+/// it is short enough. Paths with a NUL byte other than a trailing one fail
+/// with [Errno::EINVAL].
 #[cfg(feature = "std")]
 #[inline(always)]
 pub(crate) fn run_with_cstr<P, T, F>(path: P, f: F) -> Result<T, Errno>
@@ -472,10 +539,6 @@ where
     F: FnOnce(&CStr) -> Result<T, Errno>,
 {
     use core::mem::MaybeUninit;
-    #[cfg(extern_cstr)]
-    use cstr_core::CString;
-    #[cfg(not(extern_cstr))]
-    use std::ffi::CString;
     use std::os::unix::ffi::OsStrExt;
 
     #[cfg(not(target_os = "espidf"))]
@@ -490,11 +553,11 @@ where
     }
 
     if path.last().map(|&c| c == 0).unwrap_or(false) {
-        return f(CStr::from_bytes_with_nul(path).map_err(|_| Errno::ENOENT)?);
+        return f(CStr::from_bytes_with_nul(path).map_err(|_| Errno::EINVAL)?);
     }
 
     if path.len() >= MAX_STACK_ALLOCATION {
-        return CString::new(path).map_or(Err(Errno::ENOENT), |path| f(&path));
+        return CString::new(path).map_or(Err(Errno::EINVAL), |path| f(&path));
     }
 
     let mut buf = MaybeUninit::<[u8; MAX_STACK_ALLOCATION]>::uninit();
@@ -506,7 +569,7 @@ where
     }
 
     CStr::from_bytes_with_nul(unsafe { core::slice::from_raw_parts(buf_ptr, path.len() + 1) })
-        .map_or(Err(Errno::ENOENT), f)
+        .map_or(Err(Errno::EINVAL), f)
 }
 
 #[cfg(not(feature = "std"))]
@@ -546,21 +609,45 @@ pub unsafe fn fstatat<P: AsRef<Path>>(
 /// This function is marked as unsafe because directory file descriptor
 /// (`dirfd`) cannot be checked.
 #[cfg(all(not(feature = "linux_4_11"), not(target_arch = "loongarch64")))]
+#[inline]
 pub unsafe fn fstatat_cstr(dirfd: RawFd, path: &CStr, flags: StatAtFlags) -> Result<Stat, Errno> {
+    fstatat_traced(dirfd, path, flags, RetryPolicy::global()).0
+}
+
+/// Single attempt of [crate::fstatat_cstr], also returns the system call
+/// which produced the result and whether it was a fallback from `statx`.
+#[cfg(all(not(feature = "linux_4_11"), not(target_arch = "loongarch64")))]
+unsafe fn fstatat_once(
+    dirfd: RawFd,
+    path: &CStr,
+    flags: StatAtFlags,
+) -> (Result<Stat, Errno>, StatOperation, bool) {
     use core::sync::atomic::Ordering;
 
     match HAS_STATX.load(Ordering::Relaxed) {
-        0 => crate::raw::fstatat_cstr(dirfd, path, flags).map(Stat::Stat64),
-        1 => crate::raw::statx_cstr(dirfd, path, flags, crate::raw::StatXMask::BASIC_STATS)
-            .map(Stat::Statx),
+        0 => (
+            crate::raw::fstatat_cstr(dirfd, path, flags).map(Stat::Stat64),
+            StatOperation::Fstatat,
+            true,
+        ),
+        1 => (
+            crate::raw::statx_cstr(dirfd, path, flags, crate::raw::StatXMask::BASIC_STATS)
+                .map(Stat::Statx),
+            StatOperation::Statx,
+            false,
+        ),
         _ => match crate::raw::statx_cstr(dirfd, path, flags, crate::raw::StatXMask::BASIC_STATS) {
             Err(Errno::ENOSYS) => {
                 HAS_STATX.store(0, Ordering::Relaxed);
-                crate::raw::fstatat_cstr(dirfd, path, flags).map(Stat::Stat64)
+                (
+                    crate::raw::fstatat_cstr(dirfd, path, flags).map(Stat::Stat64),
+                    StatOperation::Fstatat,
+                    true,
+                )
             }
             other => {
                 HAS_STATX.store(1, Ordering::Relaxed);
-                other.map(Stat::Statx)
+                (other.map(Stat::Statx), StatOperation::Statx, false)
             }
         },
     }
@@ -594,7 +681,77 @@ pub unsafe fn fstatat<P: AsRef<Path>>(
 #[cfg(any(feature = "linux_4_11", target_arch = "loongarch64"))]
 #[inline]
 pub unsafe fn fstatat_cstr(dirfd: RawFd, path: &CStr, flags: StatAtFlags) -> Result<Stat, Errno> {
-    raw::statx_cstr(dirfd, path, flags, crate::raw::StatXMask::empty())
+    fstatat_traced(dirfd, path, flags, RetryPolicy::global()).0
+}
+
+/// Single attempt of [crate::fstatat_cstr], also returns the system call
+/// which produced the result and whether it was a fallback from `statx`.
+#[cfg(any(feature = "linux_4_11", target_arch = "loongarch64"))]
+#[inline]
+unsafe fn fstatat_once(
+    dirfd: RawFd,
+    path: &CStr,
+    flags: StatAtFlags,
+) -> (Result<Stat, Errno>, StatOperation, bool) {
+    (
+        raw::statx_cstr(dirfd, path, flags, crate::raw::StatXMask::empty()),
+        StatOperation::Statx,
+        false,
+    )
+}
+
+/// Implementation of [crate::fstatat_cstr] retrying on [Errno::EINTR]
+/// according to `policy`, also returns the system call which produced the
+/// result and whether it was a fallback from `statx`.
+pub(crate) unsafe fn fstatat_traced(
+    dirfd: RawFd,
+    path: &CStr,
+    flags: StatAtFlags,
+    policy: RetryPolicy,
+) -> (Result<Stat, Errno>, StatOperation, bool) {
+    let mut trace = (StatOperation::Statx, false);
+    let res = policy.retry(|| {
+        let (res, operation, fallback) = fstatat_once(dirfd, path, flags);
+        trace = (operation, fallback);
+        res
+    });
+    (res, trace.0, trace.1)
+}
+
+/// Like [crate::fstatat] but retry on [Errno::EINTR] according to `policy`
+/// instead of the global [RetryPolicy].
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+#[inline]
+pub unsafe fn fstatat_with_retry<P: AsRef<Path>>(
+    dirfd: RawFd,
+    path: P,
+    flags: StatAtFlags,
+    policy: RetryPolicy,
+) -> Result<Stat, Errno> {
+    run_with_cstr(path, |path| {
+        fstatat_with_retry_cstr(dirfd, path, flags, policy)
+    })
+}
+
+/// Like [crate::fstatat] but retry on [Errno::EINTR] according to `policy`
+/// instead of the global [RetryPolicy]. Accept `path` as a [CStr].
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+#[inline]
+pub unsafe fn fstatat_with_retry_cstr(
+    dirfd: RawFd,
+    path: &CStr,
+    flags: StatAtFlags,
+    policy: RetryPolicy,
+) -> Result<Stat, Errno> {
+    fstatat_traced(dirfd, path, flags, policy).0
 }
 
 /// Call [crate::fstatat] for `path` in the current directory
@@ -654,15 +811,61 @@ pub(crate) mod tests {
         unsafe { CStr::from_bytes_with_nul_unchecked(b"/dev/null\0") }
     }
 
-    pub fn retry<T, F: Fn() -> Result<T, Errno>>(f: F) -> Result<T, Errno> {
-        loop {
-            match f() {
-                Err(Errno::EINTR) => (),
-                other => return other,
+    /// Temporary directory removed with its content on drop, even if the
+    /// test panics.
+    #[cfg(feature = "std")]
+    pub struct TempDir(std::path::PathBuf);
+
+    #[cfg(feature = "std")]
+    impl TempDir {
+        /// Create a new directory `linux-stat-<name>-<pid>-<n>` in the
+        /// temporary directory, `n` makes it unique among the tests of the
+        /// process and `create_dir` among concurrent processes.
+        pub fn new(name: &str) -> Self {
+            use core::sync::atomic::{AtomicUsize, Ordering};
+
+            static COUNTER: AtomicUsize = AtomicUsize::new(0);
+            loop {
+                let path = std::env::temp_dir().join(format!(
+                    "linux-stat-{}-{}-{}",
+                    name,
+                    std::process::id(),
+                    COUNTER.fetch_add(1, Ordering::Relaxed)
+                ));
+                match std::fs::create_dir(&path) {
+                    Ok(()) => return Self(path),
+                    Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => (),
+                    Err(err) => panic!("cannot create {:?}: {}", path, err),
+                }
             }
         }
     }
 
+    #[cfg(feature = "std")]
+    impl core::ops::Deref for TempDir {
+        type Target = Path;
+
+        #[inline]
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl AsRef<Path> for TempDir {
+        #[inline]
+        fn as_ref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
     #[cfg(target_os = "android")]
     pub use libc::__errno as errno;
     #[cfg(target_os = "linux")]
@@ -717,4 +920,15 @@ pub(crate) mod tests {
         assert_eq!(stat.ctime().secs, c_stat.st_ctime as i64);
         assert_eq!(stat.ctime().nsecs, c_stat.st_ctime_nsec as u32);
     }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn interior_nul() {
+        linux_syscalls::init();
+
+        let long = format!("/dev/{}\0null", "x".repeat(512));
+        for path in ["/dev\0/null", "/dev\0/null\0", long.as_str()] {
+            assert_eq!(stat(path).map(|_| ()), Err(Errno::EINVAL), "{:?}", path);
+        }
+    }
 }
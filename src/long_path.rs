@@ -0,0 +1,208 @@
+use linux_raw_sys::general::{O_CLOEXEC, O_DIRECTORY, O_PATH, PATH_MAX};
+use linux_syscalls::Errno;
+
+use crate::{
+    fstatat_cstr, run_with_cstr, sys, CStr, Path, RawFd, Stat, StatAtFlags, CURRENT_DIRECTORY,
+};
+
+const CHUNK_MAX: usize = PATH_MAX as usize - 1;
+
+/// Split `path` (longer than [CHUNK_MAX]) in a leading chunk which fits in
+/// `PATH_MAX` and the rest, without leading slashes.
+fn split(path: &[u8]) -> Result<(&[u8], &[u8]), Errno> {
+    let i = path[..CHUNK_MAX]
+        .iter()
+        .rposition(|&c| c == b'/')
+        .ok_or(Errno::ENAMETOOLONG)?;
+    let head = if i == 0 { &path[..1] } else { &path[..i] };
+    let mut rest = &path[i + 1..];
+    while let Some((b'/', tail)) = rest.split_first() {
+        rest = tail;
+    }
+    Ok((head, rest))
+}
+
+#[inline]
+fn to_cstr<'a>(path: &[u8], buf: &'a mut [u8; PATH_MAX as usize]) -> &'a CStr {
+    buf[..path.len()].copy_from_slice(path);
+    buf[path.len()] = 0;
+    unsafe { CStr::from_bytes_with_nul_unchecked(&buf[..=path.len()]) }
+}
+
+/// Like [crate::fstatat] but `path` can be longer than `PATH_MAX`.
+///
+/// Long paths are split at `/` in chunks shorter than `PATH_MAX`, every chunk
+/// but the last is opened in turn with `openat(O_PATH | O_DIRECTORY)`
+/// relative to the previous one and the last is passed to
+/// [crate::fstatat_cstr] with `flags`. Symbolic links in the intermediate
+/// chunks are always followed and `..` is resolved from the directory
+/// actually reached, like the kernel does for short paths, but the limit of
+/// 40 symbolic links applies to each chunk and not to the whole path.
+///
+/// A single component longer than `PATH_MAX` fails with
+/// [Errno::ENAMETOOLONG].
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+#[inline]
+pub unsafe fn fstatat_long<P: AsRef<Path>>(
+    dirfd: RawFd,
+    path: P,
+    flags: StatAtFlags,
+) -> Result<Stat, Errno> {
+    run_with_cstr(path, |path| fstatat_long_cstr(dirfd, path, flags))
+}
+
+/// Like [crate::fstatat] but `path` can be longer than `PATH_MAX`.
+/// Accept `path` as a [CStr].
+///
+/// See [crate::fstatat_long].
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+pub unsafe fn fstatat_long_cstr(
+    dirfd: RawFd,
+    path: &CStr,
+    flags: StatAtFlags,
+) -> Result<Stat, Errno> {
+    let mut rest = path.to_bytes();
+    if rest.len() <= CHUNK_MAX {
+        return fstatat_cstr(dirfd, path, flags);
+    }
+
+    let mut buf = [0u8; PATH_MAX as usize];
+    let mut dir: Option<sys::Fd> = None;
+    while rest.len() > CHUNK_MAX {
+        let (head, tail) = split(rest)?;
+        let fd = dir.as_ref().map_or(dirfd, |fd| fd.as_raw_fd());
+        dir = Some(sys::openat(
+            fd,
+            to_cstr(head, &mut buf),
+            O_PATH | O_DIRECTORY | O_CLOEXEC,
+            0,
+        )?);
+        rest = tail;
+    }
+
+    // A trailing slash was already resolved by `O_DIRECTORY`.
+    if rest.is_empty() {
+        rest = b".";
+    }
+    let fd = dir.as_ref().map_or(dirfd, |fd| fd.as_raw_fd());
+    fstatat_cstr(fd, to_cstr(rest, &mut buf), flags)
+}
+
+/// Call [crate::fstatat_long] for `path` in the current directory
+/// following symlinks.
+#[inline]
+pub fn stat_long<P: AsRef<Path>>(path: P) -> Result<Stat, Errno> {
+    run_with_cstr(path, stat_long_cstr)
+}
+
+/// Call [crate::fstatat_long] for `path` in the current directory
+/// following symlinks. Accept `path` as a [CStr].
+#[inline]
+pub fn stat_long_cstr(path: &CStr) -> Result<Stat, Errno> {
+    unsafe { fstatat_long_cstr(CURRENT_DIRECTORY, path, StatAtFlags::empty()) }
+}
+
+/// Call [crate::fstatat_long] for `path` in the current directory
+/// not following symlinks.
+#[inline]
+pub fn lstat_long<P: AsRef<Path>>(path: P) -> Result<Stat, Errno> {
+    run_with_cstr(path, lstat_long_cstr)
+}
+
+/// Call [crate::fstatat_long] for `path` in the current directory
+/// not following symlinks. Accept `path` as a [CStr].
+#[inline]
+pub fn lstat_long_cstr(path: &CStr) -> Result<Stat, Errno> {
+    unsafe { fstatat_long_cstr(CURRENT_DIRECTORY, path, StatAtFlags::SYMLINK_NOFOLLOW) }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::{
+        ffi::CString,
+        os::unix::{ffi::OsStrExt, io::AsRawFd},
+    };
+
+    use super::*;
+    use crate::{retry, FileId};
+
+    #[test]
+    fn long_paths() {
+        linux_syscalls::init();
+
+        let root = crate::tests::TempDir::new("long");
+
+        // Build `root/ddd.../ddd.../...` deeper than PATH_MAX with `mkdirat`.
+        let name = "d".repeat(200);
+        let cname = CString::new(name.as_bytes()).unwrap();
+        let mut dir = std::fs::File::open(&root).unwrap();
+        let mut path = root.to_path_buf();
+        let mut mid = None;
+        for i in 0..30 {
+            assert_eq!(
+                unsafe { libc::mkdirat(dir.as_raw_fd(), cname.as_ptr(), 0o755) },
+                0
+            );
+            let fd = unsafe {
+                libc::openat(
+                    dir.as_raw_fd(),
+                    cname.as_ptr(),
+                    libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC,
+                )
+            };
+            assert!(fd >= 0);
+            dir = unsafe { std::os::unix::io::FromRawFd::from_raw_fd(fd) };
+            path.push(&name);
+            if i == 10 {
+                mid = Some(path.clone());
+            }
+        }
+        let mid = mid.unwrap();
+        let deep = FileId::from_stat(&unsafe { crate::fstat(dir.as_raw_fd()) }.unwrap());
+        let link = CString::new("link").unwrap();
+        let target = CString::new(mid.as_os_str().as_bytes()).unwrap();
+        assert_eq!(
+            unsafe { libc::symlinkat(target.as_ptr(), dir.as_raw_fd(), link.as_ptr()) },
+            0
+        );
+        assert!(path.as_os_str().len() > PATH_MAX as usize);
+
+        assert_eq!(crate::stat(&path).map(|_| ()), Err(Errno::ENAMETOOLONG));
+        let stat = retry(|| stat_long(&path)).unwrap();
+        assert!(stat.is_dir());
+        assert_eq!(FileId::from_stat(&stat), deep);
+        let with_slash = format!("{}//", path.display());
+        assert_eq!(
+            retry(|| stat_long(&with_slash)).map(|s| FileId::from_stat(&s)),
+            Ok(deep)
+        );
+
+        let mid_id = FileId::from_stat(&crate::stat(&mid).unwrap());
+        let link_path = path.join("link");
+        assert!(retry(|| lstat_long(&link_path)).unwrap().is_link());
+        assert_eq!(
+            retry(|| stat_long(&link_path)).map(|s| FileId::from_stat(&s)),
+            Ok(mid_id)
+        );
+        assert_eq!(
+            retry(|| stat_long(link_path.join(&name).join(".."))).map(|s| FileId::from_stat(&s)),
+            Ok(mid_id)
+        );
+        assert_eq!(
+            retry(|| stat_long(path.join("missing"))).map(|_| ()),
+            Err(Errno::ENOENT)
+        );
+        assert_eq!(
+            retry(|| stat_long(root.join("x".repeat(PATH_MAX as usize)))).map(|_| ()),
+            Err(Errno::ENAMETOOLONG)
+        );
+    }
+}
@@ -0,0 +1,559 @@
+use core::{fmt, str::FromStr};
+
+use linux_raw_sys::general::{S_ISGID, S_ISUID, S_ISVTX};
+
+use crate::{FileType, Mode, ModePermission};
+
+impl FileType {
+    /// Returns the character used by `ls -l` for this file type (`-`, `d`,
+    /// `l`, `b`, `c`, `p`, `s` or `?`).
+    #[inline]
+    pub const fn as_char(&self) -> char {
+        match *self {
+            FileType::Block => 'b',
+            FileType::Character => 'c',
+            FileType::Directory => 'd',
+            FileType::Fifo => 'p',
+            FileType::Link => 'l',
+            FileType::Regular => '-',
+            FileType::Socket => 's',
+            FileType::Unknown => '?',
+        }
+    }
+}
+
+impl Mode {
+    /// Returns an object that implements [core::fmt::Display] printing the
+    /// mode prefixed by the file type character, like `ls -l` (e.g.
+    /// `drwxr-xr-x`).
+    #[inline]
+    pub const fn display(&self, file_type: FileType) -> DisplayMode {
+        DisplayMode {
+            mode: *self,
+            file_type,
+        }
+    }
+}
+
+/// Helper struct for printing a [Mode] with its [FileType], see
+/// [Mode::display].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DisplayMode {
+    mode: Mode,
+    file_type: FileType,
+}
+
+impl fmt::Display for DisplayMode {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.file_type.as_char(), self.mode)
+    }
+}
+
+impl fmt::Display for Mode {
+    /// Formats the mode as `ls -l` does, without the file type character
+    /// (e.g. `rwsr-xr-T`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn format_perm(
+            f: &mut fmt::Formatter<'_>,
+            p: ModePermission,
+            special: bool,
+            c: char,
+        ) -> fmt::Result {
+            let x = p.contains(ModePermission::EXEC);
+            let mut buf = [b'-'; 3];
+            if p.contains(ModePermission::READ) {
+                buf[0] = b'r';
+            }
+            if p.contains(ModePermission::WRITE) {
+                buf[1] = b'w';
+            }
+            buf[2] = match (special, x) {
+                (true, true) => c as u8,
+                (true, false) => c.to_ascii_uppercase() as u8,
+                (false, true) => b'x',
+                (false, false) => b'-',
+            };
+            // SAFETY: buf contains only ASCII characters.
+            f.write_str(unsafe { core::str::from_utf8_unchecked(&buf) })
+        }
+
+        format_perm(f, self.owner(), self.suid(), 's')?;
+        format_perm(f, self.group(), self.sgid(), 's')?;
+        format_perm(f, self.other(), self.svtx(), 't')
+    }
+}
+
+/// An error which can be returned when parsing a [Mode].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseModeError(());
+
+impl fmt::Display for ParseModeError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid file mode")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseModeError {}
+
+fn parse_octal(s: &str) -> Result<Mode, ParseModeError> {
+    if s.is_empty() || !s.bytes().all(|c| (b'0'..=b'7').contains(&c)) {
+        return Err(ParseModeError(()));
+    }
+    match u16::from_str_radix(s, 8) {
+        Ok(mode) if mode <= 0o7777 => Ok(Mode(mode)),
+        _ => Err(ParseModeError(())),
+    }
+}
+
+fn parse_ls(s: &[u8]) -> Result<Mode, ParseModeError> {
+    const SPECIAL: [(u16, u8); 3] = [
+        (S_ISUID as u16, b's'),
+        (S_ISGID as u16, b's'),
+        (S_ISVTX as u16, b't'),
+    ];
+
+    let mut mode = 0u16;
+    for (i, (chunk, (special, c))) in s.chunks(3).zip(SPECIAL.iter()).enumerate() {
+        let shift = 6 - 3 * i as u16;
+        mode |= match chunk[0] {
+            b'r' => 0o4 << shift,
+            b'-' => 0,
+            _ => return Err(ParseModeError(())),
+        };
+        mode |= match chunk[1] {
+            b'w' => 0o2 << shift,
+            b'-' => 0,
+            _ => return Err(ParseModeError(())),
+        };
+        mode |= match chunk[2] {
+            b'x' => 0o1 << shift,
+            b'-' => 0,
+            x if x == *c => *special | (0o1 << shift),
+            x if x == c.to_ascii_uppercase() => *special,
+            _ => return Err(ParseModeError(())),
+        };
+    }
+    Ok(Mode(mode))
+}
+
+fn parse_symbolic(s: &str) -> Result<Mode, ParseModeError> {
+    let mut mode = 0u16;
+    for clause in s.split(',') {
+        let (who, perms) = clause.split_once('=').ok_or(ParseModeError(()))?;
+        if who.is_empty() {
+            return Err(ParseModeError(()));
+        }
+
+        let mut mask = 0u16;
+        for c in who.bytes() {
+            mask |= match c {
+                b'u' => S_ISUID as u16 | 0o700,
+                b'g' => S_ISGID as u16 | 0o070,
+                b'o' => S_ISVTX as u16 | 0o007,
+                b'a' => 0o7777,
+                _ => return Err(ParseModeError(())),
+            };
+        }
+
+        let mut bits = 0u16;
+        for c in perms.bytes() {
+            bits |= match c {
+                b'r' => 0o444,
+                b'w' => 0o222,
+                b'x' => 0o111,
+                b's' => (S_ISUID | S_ISGID) as u16,
+                b't' => S_ISVTX as u16,
+                _ => return Err(ParseModeError(())),
+            };
+        }
+
+        mode = (mode & !mask) | (bits & mask);
+    }
+    Ok(Mode(mode))
+}
+
+impl FromStr for Mode {
+    type Err = ParseModeError;
+
+    /// Parse a [Mode] in octal (e.g. `0755`), in the `ls -l` form with or
+    /// without the file type character (e.g. `rwsr-xr-T`, `-rw-r--r--`) or
+    /// as comma separated `who=perms` clauses (e.g. `u=rwx,g=rx,o=rx`).
+    ///
+    /// The file type character of the `ls -l` form is validated and
+    /// discarded.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = s.as_bytes();
+        if bytes.first().map_or(false, u8::is_ascii_digit) {
+            parse_octal(s)
+        } else if s.contains('=') {
+            parse_symbolic(s)
+        } else if bytes.len() == 10 && b"-dlbcps?".contains(&bytes[0]) {
+            parse_ls(&bytes[1..])
+        } else if bytes.len() == 9 {
+            parse_ls(bytes)
+        } else {
+            Err(ParseModeError(()))
+        }
+    }
+}
+
+/// Mode bits affected by chmod(1).
+const CHMOD_MODE_BITS: u16 = 0o7777;
+const S_ISUGID: u16 = (S_ISUID | S_ISGID) as u16;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ChangeFlag {
+    /// Set, add or remove the bits in `value`.
+    Ordinary,
+    /// Copy the bits in `value` from the current mode (`g=u`).
+    CopyExisting,
+    /// Like `Ordinary` but also affect the exec bits if any exec bit is
+    /// already set or the file is a directory (`X`).
+    XIfAnyX,
+}
+
+struct Change {
+    op: u8,
+    flag: ChangeFlag,
+    affected: u16,
+    value: u16,
+    mentioned: u16,
+}
+
+impl Change {
+    /// Apply the change to `mode` like gnulib's `mode_adjust`.
+    fn apply(&self, mode: u16, dir: bool, umask: u16) -> u16 {
+        let omit_change = if dir { S_ISUGID & !self.mentioned } else { 0 };
+        let mut value = self.value;
+
+        match self.flag {
+            ChangeFlag::Ordinary => (),
+            ChangeFlag::CopyExisting => {
+                value &= mode;
+                value |= (if value & 0o444 != 0 { 0o444 } else { 0 })
+                    | (if value & 0o222 != 0 { 0o222 } else { 0 })
+                    | (if value & 0o111 != 0 { 0o111 } else { 0 });
+            }
+            ChangeFlag::XIfAnyX => {
+                if mode & 0o111 != 0 || dir {
+                    value |= 0o111;
+                }
+            }
+        }
+
+        // Without who the umask applies.
+        value &= if self.affected != 0 {
+            self.affected
+        } else {
+            !umask
+        } & !omit_change;
+
+        match self.op {
+            b'=' => {
+                let preserved = if self.affected != 0 {
+                    !self.affected
+                } else {
+                    0
+                } | omit_change;
+                (mode & preserved) | value
+            }
+            b'+' => mode | value,
+            _ => mode & !value,
+        }
+    }
+}
+
+fn parse_octal_digits(s: &[u8], i: &mut usize) -> Result<u16, ParseModeError> {
+    let mut value = 0u16;
+    while let Some(c @ b'0'..=b'7') = s.get(*i) {
+        value = value * 8 + (c - b'0') as u16;
+        if value > CHMOD_MODE_BITS {
+            return Err(ParseModeError(()));
+        }
+        *i += 1;
+    }
+    Ok(value)
+}
+
+impl Mode {
+    /// Returns the mode resulting from applying the chmod(1) expression
+    /// `expr` (e.g. `u+rwx,g-w,o=`, `a+X`, `+t`, `g=u` or `0755`) to this
+    /// mode, with the same semantics as GNU coreutils.
+    ///
+    /// `file_type` is used for the conditional `X` and to preserve the
+    /// set-user-ID and set-group-ID bits of directories unless explicitly
+    /// mentioned. `umask` masks the permissions set by clauses without
+    /// who (e.g. `+x`, `=r`).
+    pub fn chmod(
+        &self,
+        expr: &str,
+        file_type: FileType,
+        umask: Mode,
+    ) -> Result<Self, ParseModeError> {
+        let s = expr.as_bytes();
+        let dir = file_type == FileType::Directory;
+        let umask = umask.0 & CHMOD_MODE_BITS;
+        let mut mode = self.0 & CHMOD_MODE_BITS;
+        let mut i = 0;
+
+        if let Some(b'0'..=b'7') = s.first() {
+            let value = parse_octal_digits(s, &mut i)?;
+            if i != s.len() {
+                return Err(ParseModeError(()));
+            }
+            // Octal modes with less than 5 digits preserve the set-user-ID
+            // and set-group-ID bits of directories.
+            let mentioned = if s.len() < 5 {
+                (value & S_ISUGID) | S_ISVTX as u16 | 0o777
+            } else {
+                CHMOD_MODE_BITS
+            };
+            let change = Change {
+                op: b'=',
+                flag: ChangeFlag::Ordinary,
+                affected: CHMOD_MODE_BITS,
+                value,
+                mentioned,
+            };
+            return Ok(Self(change.apply(mode, dir, umask)));
+        }
+
+        loop {
+            let mut affected = 0u16;
+            loop {
+                affected |= match s.get(i) {
+                    Some(b'u') => S_ISUID as u16 | 0o700,
+                    Some(b'g') => S_ISGID as u16 | 0o070,
+                    Some(b'o') => S_ISVTX as u16 | 0o007,
+                    Some(b'a') => CHMOD_MODE_BITS,
+                    Some(b'=' | b'+' | b'-') => break,
+                    _ => return Err(ParseModeError(())),
+                };
+                i += 1;
+            }
+
+            while let Some(&op @ (b'=' | b'+' | b'-')) = s.get(i) {
+                i += 1;
+
+                let mut mentioned = 0;
+                let mut flag = ChangeFlag::CopyExisting;
+                let mut value = 0;
+                match s.get(i) {
+                    Some(b'0'..=b'7') => {
+                        value = parse_octal_digits(s, &mut i)?;
+                        if affected != 0 || !matches!(s.get(i), None | Some(b',')) {
+                            return Err(ParseModeError(()));
+                        }
+                        affected = CHMOD_MODE_BITS;
+                        mentioned = CHMOD_MODE_BITS;
+                        flag = ChangeFlag::Ordinary;
+                    }
+                    Some(b'u') => {
+                        value = 0o700;
+                        i += 1;
+                    }
+                    Some(b'g') => {
+                        value = 0o070;
+                        i += 1;
+                    }
+                    Some(b'o') => {
+                        value = 0o007;
+                        i += 1;
+                    }
+                    _ => {
+                        flag = ChangeFlag::Ordinary;
+                        while let Some(c) = s.get(i) {
+                            match c {
+                                b'r' => value |= 0o444,
+                                b'w' => value |= 0o222,
+                                b'x' => value |= 0o111,
+                                b'X' => flag = ChangeFlag::XIfAnyX,
+                                b's' => value |= S_ISUGID,
+                                b't' => value |= S_ISVTX as u16,
+                                _ => break,
+                            }
+                            i += 1;
+                        }
+                    }
+                }
+
+                if mentioned == 0 {
+                    mentioned = if affected != 0 {
+                        affected & value
+                    } else {
+                        value
+                    };
+                }
+                let change = Change {
+                    op,
+                    flag,
+                    affected,
+                    value,
+                    mentioned,
+                };
+                mode = change.apply(mode, dir, umask);
+            }
+
+            match s.get(i) {
+                None => return Ok(Self(mode)),
+                Some(b',') => i += 1,
+                Some(_) => return Err(ParseModeError(())),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn display() {
+        let cases = [
+            (0o755, "rwxr-xr-x"),
+            (0o644, "rw-r--r--"),
+            (0o4755, "rwsr-xr-x"),
+            (0o4644, "rwSr--r--"),
+            (0o2750, "rwxr-s---"),
+            (0o2740, "rwxr-S---"),
+            (0o1777, "rwxrwxrwt"),
+            (0o1776, "rwxrwxrwT"),
+            (0o7000, "--S--S--T"),
+            (0, "---------"),
+        ];
+        for (mode, s) in cases {
+            assert_eq!(Mode(mode).to_string(), s);
+            assert_eq!(format!("Mode({})", s), format!("{:?}", Mode(mode)));
+        }
+
+        assert_eq!(
+            Mode(0o1777).display(FileType::Directory).to_string(),
+            "drwxrwxrwt"
+        );
+        assert_eq!(
+            Mode(0o644).display(FileType::Regular).to_string(),
+            "-rw-r--r--"
+        );
+        assert_eq!(
+            Mode(0o777).display(FileType::Link).to_string(),
+            "lrwxrwxrwx"
+        );
+    }
+
+    #[test]
+    fn from_str() {
+        let ok = [
+            ("0755", 0o755),
+            ("755", 0o755),
+            ("4755", 0o4755),
+            ("0", 0),
+            ("rwsr-xr-T", 0o5754),
+            ("rwSr--r--", 0o4644),
+            ("-rw-r--r--", 0o644),
+            ("drwxrwxrwt", 0o1777),
+            ("---------", 0),
+            ("u=rwx,g=rx,o=rx", 0o755),
+            ("a=r,u=rw", 0o644),
+            ("ug=rwxs,o=", 0o6770),
+            ("a=rwxt", 0o1777),
+            ("u=", 0),
+        ];
+        for (s, mode) in ok {
+            assert_eq!(s.parse::<Mode>(), Ok(Mode(mode)), "{:?}", s);
+        }
+
+        let err = [
+            "",
+            "8",
+            "0o755",
+            "17777",
+            "rwxr-xr-",
+            "rwxr-xr-xx",
+            "xwrr-xr-x",
+            "rwtr-xr-x",
+            "rwxr-xr-s",
+            "Drwxr-xr-x",
+            "u+rwx",
+            "=rwx",
+            "u=rwz",
+            "k=r",
+            "u=r,",
+        ];
+        for s in err {
+            assert_eq!(s.parse::<Mode>(), Err(ParseModeError(())), "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn chmod() {
+        use FileType::{Directory, Regular};
+
+        // (mode, file type, umask, expression, expected), the expected
+        // values are the output of GNU coreutils 9.1.
+        #[rustfmt::skip]
+        let cases = [
+            (0o644, Regular, 0o022, "u+rwx,g-w,o=", 0o740),
+            (0o644, Regular, 0o022, "a+X", 0o644),
+            (0o744, Regular, 0o022, "a+X", 0o755),
+            (0o644, Directory, 0o022, "a+X", 0o755),
+            (0o755, Directory, 0o022, "+t", 0o1755),
+            (0o755, Regular, 0o022, "g+s", 0o2755),
+            (0o644, Regular, 0o022, "+x", 0o755),
+            (0o644, Regular, 0o077, "+x", 0o744),
+            (0o000, Regular, 0o022, "=rw", 0o644),
+            (0o777, Regular, 0o022, "=r", 0o444),
+            (0o777, Regular, 0o022, "-w", 0o577),
+            (0o666, Regular, 0o002, "-w", 0o446),
+            (0o640, Regular, 0o022, "g=u", 0o660),
+            (0o750, Regular, 0o022, "o=g", 0o755),
+            (0o751, Regular, 0o022, "go=u-w", 0o755),
+            (0o644, Regular, 0o022, "ug=o", 0o444),
+            (0o644, Regular, 0o022, "u=rwx,g=rx,o=rx", 0o755),
+            (0o644, Regular, 0o022, "u+x,g+x,o+x", 0o755),
+            (0o600, Regular, 0o022, "a=r,u+w", 0o644),
+            (0o600, Regular, 0o022, "u-r+x", 0o300),
+            (0o640, Regular, 0o000, "o=rX", 0o644),
+            (0o000, Directory, 0o027, "=rX", 0o550),
+            (0o644, Regular, 0o022, "ug+s", 0o6644),
+            (0o644, Regular, 0o022, "o+s", 0o644),
+            (0o644, Regular, 0o022, "u+t", 0o644),
+            (0o4755, Regular, 0o022, "a-s", 0o755),
+            (0o1777, Directory, 0o022, "-t", 0o777),
+            (0o6755, Regular, 0o022, "755", 0o755),
+            (0o755, Directory, 0o022, "2755", 0o2755),
+            (0o2755, Directory, 0o022, "755", 0o2755),
+            (0o2755, Directory, 0o022, "00755", 0o755),
+            (0o2755, Directory, 0o022, "=755", 0o755),
+            (0o2755, Directory, 0o022, "u=rwx,g=rx,o=rx", 0o2755),
+            (0o2755, Directory, 0o022, "g-s", 0o755),
+            (0o4711, Directory, 0o022, "u=", 0o4011),
+            (0o2775, Directory, 0o022, "a=", 0o2000),
+            (0o2775, Directory, 0o022, "=", 0o2000),
+        ];
+        for (mode, file_type, umask, expr, expected) in cases {
+            assert_eq!(
+                Mode(mode).chmod(expr, file_type, Mode(umask)),
+                Ok(Mode(expected)),
+                "{:o} {:?}",
+                mode,
+                expr
+            );
+        }
+
+        let err = [
+            "", "u", "u+z", "u+x,", ",u+x", "8", "17777", "u=755", "=755x", "+r,k=w", "u+rw x",
+        ];
+        for expr in err {
+            assert_eq!(
+                Mode(0o644).chmod(expr, Regular, Mode(0o022)),
+                Err(ParseModeError(())),
+                "{:?}",
+                expr
+            );
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use linux_syscalls::Errno;
+
+use crate::{Dev, FileType};
+
+/// Device majors registered in the running kernel, as listed in
+/// `/proc/devices`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ProcDevices {
+    character: Vec<(u32, String)>,
+    block: Vec<(u32, String)>,
+}
+
+impl ProcDevices {
+    /// Read and parse `/proc/devices`.
+    pub fn read() -> Result<Self, Errno> {
+        std::fs::read_to_string("/proc/devices")
+            .map(|content| Self::parse(&content))
+            .map_err(|err| err.raw_os_error().map_or(Errno::EIO, Errno::new))
+    }
+
+    /// Parse the content of `/proc/devices`, malformed lines are ignored.
+    pub fn parse(content: &str) -> Self {
+        let mut res = Self::default();
+        let mut section = None;
+
+        for line in content.lines() {
+            match line.trim() {
+                "" => (),
+                "Character devices:" => section = Some(&mut res.character),
+                "Block devices:" => section = Some(&mut res.block),
+                line => {
+                    let section = match section {
+                        Some(ref mut section) => section,
+                        None => continue,
+                    };
+                    let (major, name) = match line.split_once(char::is_whitespace) {
+                        Some(parts) => parts,
+                        None => continue,
+                    };
+                    if let Ok(major) = major.parse() {
+                        section.push((major, name.trim().to_owned()));
+                    }
+                }
+            }
+        }
+
+        res
+    }
+
+    /// Returns the registered character device majors and their driver
+    /// names.
+    #[inline]
+    pub fn character(&self) -> &[(u32, String)] {
+        &self.character
+    }
+
+    /// Returns the registered block device majors and their driver names.
+    #[inline]
+    pub fn block(&self) -> &[(u32, String)] {
+        &self.block
+    }
+
+    /// Returns the name of the first driver registered for `major` with the
+    /// type `file_type` ([FileType::Block] or [FileType::Character]).
+    ///
+    /// Node paths listed in place of a driver name (e.g. `/dev/vc/0` and
+    /// `/dev/tty` for the majors 4 and 5) are skipped.
+    pub fn driver(&self, major: u32, file_type: FileType) -> Option<&str> {
+        let section = match file_type {
+            FileType::Character => &self.character,
+            FileType::Block => &self.block,
+            _ => return None,
+        };
+
+        section
+            .iter()
+            .find(|(m, name)| *m == major && !name.starts_with('/'))
+            .map(|(_, name)| name.as_str())
+    }
+
+    /// Call `f` with `/proc/devices` parsed on the first successful call and
+    /// reused afterwards, so majors registered later (e.g. by loading a
+    /// module) are not seen.
+    pub(crate) fn with_cached<R, F: FnOnce(&Self) -> R>(f: F) -> Result<R, Errno> {
+        use std::sync::Mutex;
+
+        static CACHE: Mutex<Option<ProcDevices>> = Mutex::new(None);
+
+        let mut cache = CACHE.lock().unwrap_or_else(|err| err.into_inner());
+        if cache.is_none() {
+            *cache = Some(Self::read()?);
+        }
+        Ok(f(cache.as_ref().unwrap()))
+    }
+}
+
+impl Dev {
+    /// Returns the name of the driver registered for the major of this device
+    /// with the type `file_type` on the running system, read from
+    /// `/proc/devices`.
+    ///
+    /// Use [FileType::Block] for [crate::Stat::dev] and the file type of a
+    /// device node for [crate::Stat::rdev].
+    ///
+    /// `/proc/devices` is read once and the result reused by later calls.
+    pub fn driver_name(&self, file_type: FileType) -> Result<Option<String>, Errno> {
+        ProcDevices::with_cached(|devices| {
+            devices.driver(self.major(), file_type).map(str::to_owned)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let devices = ProcDevices::parse(
+            "Character devices:\n  1 mem\n  4 /dev/vc/0\n  4 tty\n  5 /dev/tty\n136 pts\n\
+             \nBlock devices:\n  7 loop\n253 device-mapper\nbogus\n259 blkext\n",
+        );
+
+        assert_eq!(
+            devices.character(),
+            &[
+                (1, "mem".to_owned()),
+                (4, "/dev/vc/0".to_owned()),
+                (4, "tty".to_owned()),
+                (5, "/dev/tty".to_owned()),
+                (136, "pts".to_owned()),
+            ]
+        );
+        assert_eq!(devices.block().len(), 3);
+        assert_eq!(devices.driver(4, FileType::Character), Some("tty"));
+        assert_eq!(devices.driver(5, FileType::Character), None);
+        assert_eq!(devices.driver(253, FileType::Block), Some("device-mapper"));
+        assert_eq!(devices.driver(253, FileType::Character), None);
+        assert_eq!(devices.driver(1, FileType::Regular), None);
+    }
+
+    #[test]
+    fn dev_null_driver() {
+        linux_syscalls::init();
+
+        let stat = crate::retry(|| crate::stat(crate::tests::dev_null())).unwrap();
+        assert_eq!(
+            stat.rdev().driver_name(FileType::Character),
+            Ok(Some("mem".to_owned()))
+        );
+        assert_eq!(
+            Dev::from_split(4, 1).driver_name(FileType::Character),
+            Ok(Some("tty".to_owned()))
+        );
+    }
+}
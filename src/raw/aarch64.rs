@@ -143,3 +143,21 @@ impl stat {
         res
     }
 }
+
+/// `statfs()` filesystem informations representation.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct statfs64 {
+    pub(crate) f_type: i64,
+    pub(crate) f_bsize: i64,
+    pub(crate) f_blocks: u64,
+    pub(crate) f_bfree: u64,
+    pub(crate) f_bavail: u64,
+    pub(crate) f_files: u64,
+    pub(crate) f_ffree: u64,
+    pub(crate) f_fsid: [i32; 2],
+    pub(crate) f_namelen: i64,
+    pub(crate) f_frsize: i64,
+    pub(crate) f_flags: i64,
+    f_spare: [i64; 4],
+}
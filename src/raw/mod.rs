@@ -8,45 +8,28 @@ use linux_raw_sys::general::{
 
 use crate::{CStr, Dev, DevSplit, FileType, Mode, RawFd, StatAtFlags, Timestamp};
 
-#[cfg_attr(
-    all(not(feature = "linux_4_11"), target_arch = "aarch64"),
-    path = "aarch64.rs"
-)]
-#[cfg_attr(all(not(feature = "linux_4_11"), target_arch = "arm"), path = "arm.rs")]
-#[cfg_attr(
-    all(not(feature = "linux_4_11"), target_arch = "mips"),
-    path = "mips.rs"
-)]
-#[cfg_attr(
-    all(not(feature = "linux_4_11"), target_arch = "mips64"),
-    path = "mips64.rs"
-)]
-#[cfg_attr(
-    all(not(feature = "linux_4_11"), target_arch = "powerpc"),
-    path = "powerpc.rs"
-)]
-#[cfg_attr(
-    all(not(feature = "linux_4_11"), target_arch = "powerpc64"),
-    path = "powerpc64.rs"
-)]
-#[cfg_attr(
-    all(not(feature = "linux_4_11"), target_arch = "riscv64"),
-    path = "riscv64.rs"
-)]
-#[cfg_attr(
-    all(not(feature = "linux_4_11"), target_arch = "s390x"),
-    path = "s390x.rs"
-)]
-#[cfg_attr(all(not(feature = "linux_4_11"), target_arch = "x86"), path = "x86.rs")]
-#[cfg_attr(
-    all(not(feature = "linux_4_11"), target_arch = "x86_64"),
-    path = "x86_64.rs"
-)]
+#[cfg_attr(target_arch = "aarch64", path = "aarch64.rs")]
+#[cfg_attr(target_arch = "arm", path = "arm.rs")]
+#[cfg_attr(target_arch = "loongarch64", path = "loongarch64.rs")]
+#[cfg_attr(target_arch = "mips", path = "mips.rs")]
+#[cfg_attr(target_arch = "mips64", path = "mips64.rs")]
+#[cfg_attr(target_arch = "powerpc", path = "powerpc.rs")]
+#[cfg_attr(target_arch = "powerpc64", path = "powerpc64.rs")]
+#[cfg_attr(target_arch = "riscv32", path = "riscv32.rs")]
+#[cfg_attr(target_arch = "riscv64", path = "riscv64.rs")]
+#[cfg_attr(target_arch = "s390x", path = "s390x.rs")]
+#[cfg_attr(target_arch = "x86", path = "x86.rs")]
+#[cfg_attr(target_arch = "x86_64", path = "x86_64.rs")]
+// With `linux_4_11` only the `statfs64` layout is used, `stat` is read with
+// `statx`.
+#[cfg_attr(feature = "linux_4_11", allow(dead_code))]
 mod stat_imp;
 
 #[cfg(all(not(feature = "linux_4_11"), not(target_arch = "loongarch64")))]
 pub use stat_imp::stat;
 
+pub use stat_imp::statfs64;
+
 use linux_syscalls::{bitflags, syscall, Errno, Sysno};
 
 bitflags! {
@@ -192,7 +175,7 @@ pub struct Statx {
 }
 
 #[inline(always)]
-const fn file_type(mode: u16) -> FileType {
+pub(crate) const fn file_type(mode: u16) -> FileType {
     match mode as u32 & S_IFMT {
         S_IFSOCK => FileType::Socket,
         S_IFLNK => FileType::Link,
@@ -206,6 +189,12 @@ const fn file_type(mode: u16) -> FileType {
 }
 
 impl Statx {
+    /// Returns the mask of the fields actually filled by the kernel.
+    #[inline]
+    pub const fn mask(&self) -> StatXMask {
+        self.stx_mask
+    }
+
     /// Returns the "preferred" block size for efficient filesystem I/O.
     /// (Writing to a file in smaller chunks may cause an inefficient
     /// read-modify-rewrite.)
@@ -681,6 +670,146 @@ pub unsafe fn statx_cstr(
     Ok(buf.assume_init())
 }
 
+#[allow(clippy::unnecessary_cast)]
+impl statfs64 {
+    #[doc(hidden)]
+    #[inline]
+    pub fn uninit() -> MaybeUninit<Self> {
+        MaybeUninit::uninit()
+    }
+
+    /// Returns the type of filesystem (a magic number, see `statfs(2)`).
+    #[inline]
+    pub const fn fs_type(&self) -> u64 {
+        self.f_type as u64
+    }
+
+    /// Returns the optimal transfer block size.
+    #[inline]
+    pub const fn block_size(&self) -> u64 {
+        self.f_bsize as u64
+    }
+
+    /// Returns the fragment size, the unit of [statfs64::blocks],
+    /// [statfs64::blocks_free] and [statfs64::blocks_available].
+    #[inline]
+    pub const fn fragment_size(&self) -> u64 {
+        self.f_frsize as u64
+    }
+
+    /// Returns the total number of data blocks in the filesystem.
+    #[inline]
+    pub const fn blocks(&self) -> u64 {
+        self.f_blocks as u64
+    }
+
+    /// Returns the number of free blocks in the filesystem.
+    #[inline]
+    pub const fn blocks_free(&self) -> u64 {
+        self.f_bfree as u64
+    }
+
+    /// Returns the number of free blocks available to unprivileged users.
+    #[inline]
+    pub const fn blocks_available(&self) -> u64 {
+        self.f_bavail as u64
+    }
+
+    /// Returns the total number of inodes in the filesystem.
+    #[inline]
+    pub const fn files(&self) -> u64 {
+        self.f_files as u64
+    }
+
+    /// Returns the number of free inodes in the filesystem.
+    #[inline]
+    pub const fn files_free(&self) -> u64 {
+        self.f_ffree as u64
+    }
+
+    /// Returns the filesystem ID.
+    #[inline]
+    pub const fn fsid(&self) -> [i32; 2] {
+        self.f_fsid
+    }
+
+    /// Returns the maximum length of filenames.
+    #[inline]
+    pub const fn name_max(&self) -> u64 {
+        self.f_namelen as u64
+    }
+
+    /// Returns the mount flags of the filesystem.
+    #[inline]
+    pub const fn flags(&self) -> crate::MountFlags {
+        crate::MountFlags::from_bits(self.f_flags as u32)
+    }
+}
+
+impl fmt::Debug for statfs64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("statfs64")
+            .field("fs_type", &self.fs_type())
+            .field("block_size", &self.block_size())
+            .field("fragment_size", &self.fragment_size())
+            .field("blocks", &self.blocks())
+            .field("blocks_free", &self.blocks_free())
+            .field("blocks_available", &self.blocks_available())
+            .field("files", &self.files())
+            .field("files_free", &self.files_free())
+            .field("fsid", &self.fsid())
+            .field("name_max", &self.name_max())
+            .field("flags", &self.flags())
+            .finish()
+    }
+}
+
+/// Invoke `statfs` system call (`statfs64` on 32-bit architectures).
+#[inline]
+pub fn statfs<P: AsRef<crate::Path>>(path: P) -> Result<statfs64, Errno> {
+    crate::run_with_cstr(path, statfs_cstr)
+}
+
+/// Invoke `statfs` system call (`statfs64` on 32-bit architectures) with
+/// `path` as a [crate::CStr].
+#[inline]
+pub fn statfs_cstr(path: &CStr) -> Result<statfs64, Errno> {
+    let mut buf = statfs64::uninit();
+    unsafe {
+        #[cfg(target_pointer_width = "64")]
+        syscall!(Sysno::statfs, path.as_ptr(), buf.as_mut_ptr())?;
+        #[cfg(target_pointer_width = "32")]
+        syscall!(
+            Sysno::statfs64,
+            path.as_ptr(),
+            core::mem::size_of::<statfs64>(),
+            buf.as_mut_ptr()
+        )?;
+        Ok(buf.assume_init())
+    }
+}
+
+/// Invoke `fstatfs` system call (`fstatfs64` on 32-bit architectures).
+///
+/// # Safety
+///
+/// This functions is inherently unsafe because it just wrap the system call
+/// and file descriptor (`fd`) cannot be checked.
+#[inline]
+pub unsafe fn fstatfs(fd: RawFd) -> Result<statfs64, Errno> {
+    let mut buf = statfs64::uninit();
+    #[cfg(target_pointer_width = "64")]
+    syscall!(Sysno::fstatfs, fd, buf.as_mut_ptr())?;
+    #[cfg(target_pointer_width = "32")]
+    syscall!(
+        Sysno::fstatfs64,
+        fd,
+        core::mem::size_of::<statfs64>(),
+        buf.as_mut_ptr()
+    )?;
+    Ok(buf.assume_init())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -691,11 +820,11 @@ mod tests {
     fn stat64_dev_null() {
         linux_syscalls::init();
 
-        let c_stat = crate::tests::retry(crate::tests::c_stat);
+        let c_stat = crate::retry(crate::tests::c_stat);
         assert!(c_stat.is_ok());
         let c_stat = c_stat.unwrap();
 
-        let stat = crate::tests::retry(|| unsafe {
+        let stat = crate::retry(|| unsafe {
             fstatat(
                 crate::CURRENT_DIRECTORY,
                 crate::tests::dev_null(),
@@ -732,11 +861,11 @@ mod tests {
     fn statx_dev_null() {
         linux_syscalls::init();
 
-        let c_stat = crate::tests::retry(crate::tests::c_stat);
+        let c_stat = crate::retry(crate::tests::c_stat);
         assert!(c_stat.is_ok());
         let c_stat = c_stat.unwrap();
 
-        let statx = crate::tests::retry(|| unsafe {
+        let statx = crate::retry(|| unsafe {
             statx(
                 crate::CURRENT_DIRECTORY,
                 crate::tests::dev_null(),
@@ -767,4 +896,32 @@ mod tests {
         assert_eq!(statx.ctime().secs, c_stat.st_ctime as i64);
         assert_eq!(statx.ctime().nsecs, c_stat.st_ctime_nsec as u32);
     }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn statfs_dev_null() {
+        linux_syscalls::init();
+
+        let c_statfs = crate::retry(|| unsafe {
+            let mut buf = MaybeUninit::<libc::statfs64>::uninit();
+            if libc::statfs64(b"/dev/null\0".as_ptr() as *const _, buf.as_mut_ptr()) == -1 {
+                return Err(Errno::new(*crate::tests::errno()));
+            }
+            Ok(buf.assume_init())
+        })
+        .unwrap();
+        let statfs = crate::retry(|| statfs(crate::tests::dev_null())).unwrap();
+
+        assert_eq!(statfs.fs_type(), c_statfs.f_type as u64);
+        assert_eq!(statfs.block_size(), c_statfs.f_bsize as u64);
+        assert_eq!(statfs.fragment_size(), c_statfs.f_frsize as u64);
+        assert_eq!(statfs.blocks(), c_statfs.f_blocks as u64);
+        assert_eq!(statfs.files(), c_statfs.f_files as u64);
+        assert_eq!(statfs.name_max(), c_statfs.f_namelen as u64);
+        assert_eq!(statfs.flags().bits(), c_statfs.f_flags as u32);
+        assert_eq!(
+            unsafe { core::mem::transmute::<libc::fsid_t, [i32; 2]>(c_statfs.f_fsid) },
+            statfs.fsid()
+        );
+    }
 }
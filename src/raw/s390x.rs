@@ -139,3 +139,21 @@ impl stat {
         res
     }
 }
+
+/// `statfs()` filesystem informations representation.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct statfs64 {
+    pub(crate) f_type: u32,
+    pub(crate) f_bsize: u32,
+    pub(crate) f_blocks: u64,
+    pub(crate) f_bfree: u64,
+    pub(crate) f_bavail: u64,
+    pub(crate) f_files: u64,
+    pub(crate) f_ffree: u64,
+    pub(crate) f_fsid: [i32; 2],
+    pub(crate) f_namelen: u32,
+    pub(crate) f_frsize: u32,
+    pub(crate) f_flags: u32,
+    f_spare: [u32; 4],
+}
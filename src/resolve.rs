@@ -0,0 +1,472 @@
+use linux_raw_sys::general::{AT_EMPTY_PATH, O_CLOEXEC, O_DIRECTORY, O_NOFOLLOW, O_PATH, PATH_MAX};
+use linux_syscalls::{bitflags, Errno};
+
+use crate::{
+    fstat, fstatat_cstr, mount_id, run_with_cstr, sys, CStr, Dev, FileId, Path, RawFd, Stat,
+    StatAtFlags, CURRENT_DIRECTORY,
+};
+
+bitflags! {
+    /// Path resolution flags for [crate::fstatat_resolve], from `openat2()`.
+    #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub enum ResolveFlags: u32 {
+        /// Disallow traversal of mount points during path resolution
+        /// (including all bind mounts).
+        NO_XDEV = linux_raw_sys::general::RESOLVE_NO_XDEV,
+        /// Disallow all magic-link resolution during path resolution (e.g.
+        /// `/proc/[pid]/fd/*`).
+        NO_MAGICLINKS = linux_raw_sys::general::RESOLVE_NO_MAGICLINKS,
+        /// Disallow resolution of symbolic links during path resolution.
+        NO_SYMLINKS = linux_raw_sys::general::RESOLVE_NO_SYMLINKS,
+        /// Do not permit the path resolution to succeed if any component of
+        /// the resolution is not a descendant of the directory indicated by
+        /// dirfd. This causes absolute symbolic links (and absolute values
+        /// of pathname) to be rejected.
+        BENEATH = linux_raw_sys::general::RESOLVE_BENEATH,
+        /// Treat the directory referred to by dirfd as the root directory
+        /// while resolving pathname. Absolute symbolic links are interpreted
+        /// relative to dirfd, `..` at the root stays at the root.
+        IN_ROOT = linux_raw_sys::general::RESOLVE_IN_ROOT,
+    }
+}
+
+const NAME_MAX: usize = linux_raw_sys::general::NAME_MAX as usize;
+
+/// Mount of a file: its device and, if known, its mount ID from `statx`
+/// (Linux 5.8) or `name_to_handle_at`.
+#[derive(Clone, Copy)]
+struct MountKey {
+    dev: Dev,
+    id: Option<u64>,
+}
+
+impl MountKey {
+    /// Returns the mount of `name` relative to `dirfd`, whose [Stat] is
+    /// `stat`, without following symbolic links.
+    unsafe fn of(dirfd: RawFd, name: &CStr, stat: &Stat) -> Self {
+        let flags = if name.to_bytes().is_empty() {
+            AT_EMPTY_PATH
+        } else {
+            0
+        };
+        Self {
+            dev: stat.dev(),
+            id: mount_id(stat).or_else(|| sys::name_to_mount_id(dirfd, name, flags)),
+        }
+    }
+
+    /// Returns the mount of the open file `fd`.
+    #[inline]
+    unsafe fn of_fd(fd: RawFd) -> Result<Self, Errno> {
+        let stat = fstat(fd)?;
+        Ok(Self::of(
+            fd,
+            CStr::from_bytes_with_nul_unchecked(b"\0"),
+            &stat,
+        ))
+    }
+
+    /// Mount IDs are compared only if both are known, otherwise only the
+    /// devices are.
+    #[inline]
+    fn same(&self, other: &Self) -> bool {
+        self.dev == other.dev
+            && match (self.id, other.id) {
+                (Some(a), Some(b)) => a == b,
+                _ => true,
+            }
+    }
+}
+
+/// State of the userspace resolution.
+struct Walk {
+    resolve: ResolveFlags,
+    root: sys::Fd,
+    root_id: FileId,
+    root_mount: MountKey,
+    /// Current directory, `None` for the root.
+    cur: Option<sys::Fd>,
+}
+
+impl Walk {
+    #[inline]
+    fn confined(&self) -> bool {
+        self.resolve.contains(ResolveFlags::BENEATH) || self.resolve.contains(ResolveFlags::IN_ROOT)
+    }
+
+    #[inline]
+    fn cur_fd(&self) -> RawFd {
+        self.cur.as_ref().unwrap_or(&self.root).as_raw_fd()
+    }
+
+    fn check_mount(&self, mount: impl FnOnce() -> Result<MountKey, Errno>) -> Result<(), Errno> {
+        if self.resolve.contains(ResolveFlags::NO_XDEV) && !mount()?.same(&self.root_mount) {
+            Err(Errno::EXDEV)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Restart from the root for absolute paths.
+    unsafe fn absolute(&mut self) -> Result<(), Errno> {
+        if self.resolve.contains(ResolveFlags::BENEATH) {
+            return Err(Errno::EXDEV);
+        }
+        if self.resolve.contains(ResolveFlags::IN_ROOT) {
+            self.cur = None;
+        } else {
+            let fd = sys::openat(
+                CURRENT_DIRECTORY,
+                CStr::from_bytes_with_nul_unchecked(b"/\0"),
+                O_PATH | O_DIRECTORY | O_CLOEXEC,
+                0,
+            )?;
+            self.check_mount(|| MountKey::of_fd(fd.as_raw_fd()))?;
+            self.cur = Some(fd);
+        }
+        Ok(())
+    }
+
+    /// Returns true if the root is `fd` or one of its ancestors.
+    unsafe fn is_beneath(&self, fd: RawFd) -> Result<bool, Errno> {
+        let dotdot = CStr::from_bytes_with_nul_unchecked(b"..\0");
+        let mut owned: Option<sys::Fd> = None;
+        let mut id = FileId::from_stat(&fstat(fd)?);
+        loop {
+            if id == self.root_id {
+                return Ok(true);
+            }
+            let cur = owned.as_ref().map_or(fd, |fd| fd.as_raw_fd());
+            let parent = sys::openat(cur, dotdot, O_PATH | O_DIRECTORY | O_CLOEXEC, 0)?;
+            let parent_id = FileId::from_stat(&fstat(parent.as_raw_fd())?);
+            if parent_id == id {
+                // Reached `/`.
+                return Ok(false);
+            }
+            id = parent_id;
+            owned = Some(parent);
+        }
+    }
+
+    unsafe fn parent(&mut self) -> Result<(), Errno> {
+        if self.confined() {
+            let at_root = match self.cur {
+                None => true,
+                Some(ref fd) => FileId::from_stat(&fstat(fd.as_raw_fd())?) == self.root_id,
+            };
+            if at_root {
+                return if self.resolve.contains(ResolveFlags::BENEATH) {
+                    Err(Errno::EXDEV)
+                } else {
+                    Ok(())
+                };
+            }
+        }
+
+        let fd = sys::openat(
+            self.cur_fd(),
+            CStr::from_bytes_with_nul_unchecked(b"..\0"),
+            O_PATH | O_DIRECTORY | O_CLOEXEC,
+            0,
+        )?;
+        // A concurrent rename may have moved the current directory outside
+        // the root.
+        if self.confined() && !self.is_beneath(fd.as_raw_fd())? {
+            return Err(Errno::EXDEV);
+        }
+        self.check_mount(|| MountKey::of_fd(fd.as_raw_fd()))?;
+        self.cur = Some(fd);
+        Ok(())
+    }
+}
+
+/// Userspace emulation of `openat2` resolution followed by `fstat`, walking
+/// `path` one component at a time with [crate::fstatat_cstr].
+unsafe fn fstatat_resolve_fallback(
+    dirfd: RawFd,
+    path: &CStr,
+    resolve: ResolveFlags,
+    flags: StatAtFlags,
+) -> Result<Stat, Errno> {
+    let path = path.to_bytes();
+    let mut buf = [0u8; PATH_MAX as usize];
+    let mut target = [0u8; PATH_MAX as usize];
+    if path.len() >= buf.len() {
+        return Err(Errno::ENAMETOOLONG);
+    }
+    buf[..path.len()].copy_from_slice(path);
+    let (mut pos, mut end) = (0, path.len());
+
+    let root = sys::openat(
+        dirfd,
+        CStr::from_bytes_with_nul_unchecked(b".\0"),
+        O_PATH | O_DIRECTORY | O_CLOEXEC,
+        0,
+    )?;
+    let root_stat = fstat(root.as_raw_fd())?;
+    let root_mount = MountKey::of(
+        root.as_raw_fd(),
+        CStr::from_bytes_with_nul_unchecked(b"\0"),
+        &root_stat,
+    );
+    let mut walk = Walk {
+        resolve,
+        root,
+        root_id: FileId::from_stat(&root_stat),
+        root_mount,
+        cur: None,
+    };
+    let mut links = 0;
+
+    loop {
+        if pos == 0 && buf.first() == Some(&b'/') && end > 0 {
+            walk.absolute()?;
+        }
+        while pos < end && buf[pos] == b'/' {
+            pos += 1;
+        }
+        if pos == end {
+            return fstat(walk.cur_fd());
+        }
+
+        let start = pos;
+        while pos < end && buf[pos] != b'/' {
+            pos += 1;
+        }
+        let last = pos == end;
+        let name = &buf[start..pos];
+
+        if name == b"." {
+            continue;
+        }
+        if name == b".." {
+            walk.parent()?;
+            continue;
+        }
+
+        if name.len() > NAME_MAX {
+            return Err(Errno::ENAMETOOLONG);
+        }
+        let mut cname = [0u8; NAME_MAX + 1];
+        cname[..name.len()].copy_from_slice(name);
+        let cname = CStr::from_bytes_with_nul_unchecked(&cname[..=name.len()]);
+
+        let stat = fstatat_cstr(walk.cur_fd(), cname, StatAtFlags::SYMLINK_NOFOLLOW)?;
+        walk.check_mount(|| Ok(MountKey::of(walk.cur_fd(), cname, &stat)))?;
+
+        if stat.is_link() && !(last && flags.contains(StatAtFlags::SYMLINK_NOFOLLOW)) {
+            if resolve.contains(ResolveFlags::NO_SYMLINKS) {
+                return Err(Errno::ELOOP);
+            }
+            links += 1;
+            if links > sys::MAXSYMLINKS {
+                return Err(Errno::ELOOP);
+            }
+
+            let len = sys::readlinkat(walk.cur_fd(), cname, &mut target)?;
+            let rest = end - pos;
+            if len + rest >= target.len() {
+                return Err(Errno::ENAMETOOLONG);
+            }
+            // The rest of the path starts with `/` (or is empty).
+            target[len..len + rest].copy_from_slice(&buf[pos..end]);
+            buf[..len + rest].copy_from_slice(&target[..len + rest]);
+            pos = 0;
+            end = len + rest;
+            continue;
+        }
+
+        if last {
+            return Ok(stat);
+        }
+        if !stat.is_dir() {
+            return Err(Errno::ENOTDIR);
+        }
+        walk.cur = Some(sys::openat(
+            walk.cur_fd(),
+            cname,
+            O_PATH | O_DIRECTORY | O_NOFOLLOW | O_CLOEXEC,
+            0,
+        )?);
+    }
+}
+
+/// Returns [Stat] of `path` relative to `dirfd` resolved like `openat2()`
+/// with the `resolve` flags (e.g. [ResolveFlags::BENEATH] or
+/// [ResolveFlags::IN_ROOT] to never escape `dirfd`).
+///
+/// `flags` can contain [StatAtFlags::SYMLINK_NOFOLLOW] to return
+/// information about the last component if it is a symbolic link and
+/// [StatAtFlags::EMPTY_PATH].
+///
+/// On kernels before 5.6 the resolution is done in userspace, one component
+/// at a time with [crate::fstatat_cstr], which is subject to these
+/// limitations:
+/// - escapes through `..` caused by concurrent renames are detected by
+///   walking up to the root after each `..`, but not atomically;
+/// - magic links (e.g. `/proc/self/fd/*`) are followed as plain symbolic
+///   links to their textual target, even without
+///   [ResolveFlags::NO_MAGICLINKS].
+/// - with [ResolveFlags::NO_XDEV] mount crossings are detected with the
+///   mount ID from `name_to_handle_at`; on filesystems without file handle
+///   support (e.g. proc) only device changes are detected, so bind mounts
+///   of the same device are not.
+///
+/// With [ResolveFlags::BENEATH] or [ResolveFlags::IN_ROOT] the kernel may
+/// return [Errno::EAGAIN] if a concurrent rename happened, the call can be
+/// retried.
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+#[inline]
+pub unsafe fn fstatat_resolve<P: AsRef<Path>>(
+    dirfd: RawFd,
+    path: P,
+    resolve: ResolveFlags,
+    flags: StatAtFlags,
+) -> Result<Stat, Errno> {
+    run_with_cstr(path, |path| {
+        fstatat_resolve_cstr(dirfd, path, resolve, flags)
+    })
+}
+
+/// Returns [Stat] of `path` relative to `dirfd` resolved like `openat2()`
+/// with the `resolve` flags. Accept `path` as a [CStr].
+///
+/// See [crate::fstatat_resolve].
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+pub unsafe fn fstatat_resolve_cstr(
+    dirfd: RawFd,
+    path: &CStr,
+    resolve: ResolveFlags,
+    flags: StatAtFlags,
+) -> Result<Stat, Errno> {
+    if path.to_bytes().is_empty() {
+        return fstatat_cstr(dirfd, path, flags);
+    }
+
+    let mut oflags = O_PATH | O_CLOEXEC;
+    if flags.contains(StatAtFlags::SYMLINK_NOFOLLOW) {
+        oflags |= O_NOFOLLOW;
+    }
+    match sys::openat2(dirfd, path, oflags, resolve.bits()) {
+        Ok(fd) => fstat(fd.as_raw_fd()),
+        Err(Errno::ENOSYS) => fstatat_resolve_fallback(dirfd, path, resolve, flags),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::retry;
+
+    #[test]
+    fn resolve() {
+        linux_syscalls::init();
+
+        let root = crate::tests::TempDir::new("resolve");
+        std::fs::create_dir_all(root.join("a/b")).unwrap();
+        std::fs::write(root.join("a/file"), b"").unwrap();
+        std::os::unix::fs::symlink("a/file", root.join("rel")).unwrap();
+        std::os::unix::fs::symlink("/a/file", root.join("abs")).unwrap();
+        std::os::unix::fs::symlink("../..", root.join("a/b/up")).unwrap();
+        std::os::unix::fs::symlink("loop", root.join("loop")).unwrap();
+
+        let dir = std::fs::File::open(&root).unwrap();
+        let dirfd = std::os::unix::io::AsRawFd::as_raw_fd(&dir);
+        let file = FileId::from_stat(&crate::stat(root.join("a/file")).unwrap());
+        let top = FileId::from_stat(&crate::stat(&root).unwrap());
+
+        let beneath = ResolveFlags::BENEATH;
+        let in_root = ResolveFlags::IN_ROOT;
+        let nofollow = StatAtFlags::SYMLINK_NOFOLLOW;
+        let none = StatAtFlags::empty();
+        let cases = [
+            ("a/file", beneath, none, Ok(file)),
+            ("./a//b/../file", beneath, none, Ok(file)),
+            ("rel", beneath, none, Ok(file)),
+            (
+                "rel",
+                beneath | ResolveFlags::NO_SYMLINKS,
+                none,
+                Err(Errno::ELOOP),
+            ),
+            ("abs", beneath, none, Err(Errno::EXDEV)),
+            ("abs", in_root, none, Ok(file)),
+            ("/a/file", beneath, none, Err(Errno::EXDEV)),
+            ("/a/file", in_root, none, Ok(file)),
+            ("..", beneath, none, Err(Errno::EXDEV)),
+            ("../../a/file", in_root, none, Ok(file)),
+            ("a/b/up", beneath, none, Ok(top)),
+            ("a/b/up/..", beneath, none, Err(Errno::EXDEV)),
+            ("a/b/up/../a/file", in_root, none, Ok(file)),
+            ("a/file/x", beneath, none, Err(Errno::ENOTDIR)),
+            ("missing", beneath, none, Err(Errno::ENOENT)),
+            ("loop", beneath, none, Err(Errno::ELOOP)),
+            ("a/file", ResolveFlags::NO_XDEV, none, Ok(file)),
+        ];
+        for (path, resolve, flags, expected) in cases {
+            let via_kernel = retry(|| unsafe { fstatat_resolve(dirfd, path, resolve, flags) });
+            let fallback = retry(|| unsafe {
+                run_with_cstr(path, |path| {
+                    fstatat_resolve_fallback(dirfd, path, resolve, flags)
+                })
+            });
+            assert_eq!(
+                via_kernel.map(|s| FileId::from_stat(&s)),
+                expected,
+                "{:?}",
+                path
+            );
+            assert_eq!(
+                fallback.map(|s| FileId::from_stat(&s)),
+                expected,
+                "fallback {:?}",
+                path
+            );
+        }
+
+        for resolve in [beneath, in_root] {
+            let stat = retry(|| unsafe { fstatat_resolve(dirfd, "rel", resolve, nofollow) });
+            assert!(stat.unwrap().is_link());
+            let stat = retry(|| unsafe {
+                fstatat_resolve_fallback(
+                    dirfd,
+                    CStr::from_bytes_with_nul_unchecked(b"rel\0"),
+                    resolve,
+                    nofollow,
+                )
+            });
+            assert!(stat.unwrap().is_link());
+        }
+
+        // The mount ID from `name_to_handle_at` matches the one of `statx`.
+        let stat = crate::stat(&root).unwrap();
+        let empty = unsafe { CStr::from_bytes_with_nul_unchecked(b"\0") };
+        if let Some(id) = mount_id(&stat) {
+            let via_handle = unsafe { sys::name_to_mount_id(dirfd, empty, AT_EMPTY_PATH) };
+            assert!(via_handle.map_or(true, |via_handle| via_handle == id));
+        }
+
+        // `/proc` is another mount.
+        let slash = std::fs::File::open("/").unwrap();
+        let slash = std::os::unix::io::AsRawFd::as_raw_fd(&slash);
+        let proc = unsafe { CStr::from_bytes_with_nul_unchecked(b"proc/self\0") };
+        let xdev = ResolveFlags::NO_XDEV;
+        assert_eq!(
+            unsafe { fstatat_resolve_cstr(slash, proc, xdev, none) }.map(|_| ()),
+            Err(Errno::EXDEV)
+        );
+        assert_eq!(
+            unsafe { fstatat_resolve_fallback(slash, proc, xdev, none) }.map(|_| ()),
+            Err(Errno::EXDEV)
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use linux_syscalls::Errno;
+
+const ALWAYS: u32 = u32::MAX;
+
+static GLOBAL_POLICY: AtomicU32 = AtomicU32::new(ALWAYS);
+
+/// What to do when a system call is interrupted by a signal ([Errno::EINTR]).
+///
+/// The functions of this crate (e.g. [crate::stat], [crate::utimensat],
+/// [crate::fchmodat], [crate::faccessat], [crate::statfs]) retry each system
+/// call according to the global policy, which defaults to
+/// [RetryPolicy::Always], [crate::fstatat_with_retry] takes a policy for a
+/// single call. The functions in [crate::raw] never retry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RetryPolicy {
+    /// Retry until the call is not interrupted.
+    Always,
+    /// Return [Errno::EINTR] to the caller.
+    Never,
+    /// Retry at most the given number of times, then return [Errno::EINTR].
+    /// `Bounded(0)` is the same as [RetryPolicy::Never] and
+    /// `Bounded(u32::MAX)` the same as [RetryPolicy::Always].
+    Bounded(u32),
+}
+
+impl Default for RetryPolicy {
+    #[inline]
+    fn default() -> Self {
+        Self::Always
+    }
+}
+
+impl RetryPolicy {
+    #[inline]
+    const fn to_raw(self) -> u32 {
+        match self {
+            Self::Always => ALWAYS,
+            Self::Never => 0,
+            Self::Bounded(n) => n,
+        }
+    }
+
+    #[inline]
+    const fn from_raw(raw: u32) -> Self {
+        match raw {
+            ALWAYS => Self::Always,
+            0 => Self::Never,
+            n => Self::Bounded(n),
+        }
+    }
+
+    /// Returns the global policy.
+    #[inline]
+    pub fn global() -> Self {
+        Self::from_raw(GLOBAL_POLICY.load(Ordering::Relaxed))
+    }
+
+    /// Set the global policy.
+    #[inline]
+    pub fn set_global(self) {
+        GLOBAL_POLICY.store(self.to_raw(), Ordering::Relaxed)
+    }
+
+    /// Call `f` and, according to the policy, call it again as long as it
+    /// returns [Errno::EINTR].
+    pub fn retry<T, F: FnMut() -> Result<T, Errno>>(self, mut f: F) -> Result<T, Errno> {
+        let mut left = self.to_raw();
+        loop {
+            match f() {
+                Err(Errno::EINTR) if left != 0 => {
+                    if left != ALWAYS {
+                        left -= 1;
+                    }
+                }
+                other => return other,
+            }
+        }
+    }
+}
+
+/// Call `f` as long as it returns [Errno::EINTR] according to the global
+/// [RetryPolicy].
+#[inline]
+pub fn retry<T, F: FnMut() -> Result<T, Errno>>(f: F) -> Result<T, Errno> {
+    RetryPolicy::global().retry(f)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interrupted(times: u32) -> impl FnMut() -> Result<u32, Errno> {
+        let mut calls = 0;
+        move || {
+            calls += 1;
+            if calls > times {
+                Ok(calls)
+            } else {
+                Err(Errno::EINTR)
+            }
+        }
+    }
+
+    #[test]
+    fn policies() {
+        assert_eq!(RetryPolicy::Always.retry(interrupted(100)), Ok(101));
+        assert_eq!(RetryPolicy::Never.retry(interrupted(1)), Err(Errno::EINTR));
+        assert_eq!(RetryPolicy::Never.retry(interrupted(0)), Ok(1));
+        assert_eq!(RetryPolicy::Bounded(3).retry(interrupted(3)), Ok(4));
+        assert_eq!(
+            RetryPolicy::Bounded(3).retry(interrupted(4)),
+            Err(Errno::EINTR)
+        );
+        assert_eq!(
+            RetryPolicy::Always.retry(|| Err::<(), _>(Errno::ENOENT)),
+            Err(Errno::ENOENT)
+        );
+
+        for policy in [
+            RetryPolicy::Always,
+            RetryPolicy::Never,
+            RetryPolicy::Bounded(7),
+        ] {
+            assert_eq!(RetryPolicy::from_raw(policy.to_raw()), policy);
+        }
+    }
+
+    #[test]
+    fn fstatat_with_policy() {
+        linux_syscalls::init();
+
+        let dev_null = crate::tests::dev_null();
+        let stat = unsafe {
+            crate::fstatat_with_retry(
+                crate::CURRENT_DIRECTORY,
+                dev_null,
+                crate::StatAtFlags::empty(),
+                RetryPolicy::Never,
+            )
+        };
+        assert_eq!(
+            stat.map(|stat| (stat.dev(), stat.inode())),
+            retry(|| crate::stat(dev_null)).map(|stat| (stat.dev(), stat.inode()))
+        );
+    }
+}
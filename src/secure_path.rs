@@ -0,0 +1,419 @@
+use std::{
+    ffi::OsStr,
+    os::unix::ffi::OsStrExt,
+    path::{Component, Path, PathBuf},
+};
+
+use linux_raw_sys::general::{O_CLOEXEC, O_NOFOLLOW, O_PATH};
+use linux_syscalls::{bitflags, Errno};
+
+use crate::{fstat, sys, StackPath, Stat, CURRENT_DIRECTORY};
+
+const NAME_MAX: usize = linux_raw_sys::general::NAME_MAX as usize;
+const PATH_MAX: usize = linux_raw_sys::general::PATH_MAX as usize;
+
+bitflags! {
+    /// Problems found on a path component by [crate::validate_path].
+    #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub enum PathIssues: u8 {
+        /// The owner is not a trusted user.
+        UNTRUSTED_OWNER = 1 << 0,
+        /// Writable by a group which is not trusted.
+        GROUP_WRITABLE = 1 << 1,
+        /// Writable by others.
+        WORLD_WRITABLE = 1 << 2,
+        /// The component is a symbolic link.
+        SYMLINK = 1 << 3,
+    }
+}
+
+/// Policy for [crate::validate_path].
+///
+/// The default policy trusts root and the effective user of the process, no
+/// group, allows writable directories with the sticky bit (e.g. `/tmp`) and
+/// rejects symbolic links.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SecurePathPolicy {
+    trusted_uids: Vec<u32>,
+    trusted_gids: Vec<u32>,
+    allow_sticky: bool,
+    allow_symlinks: bool,
+}
+
+impl Default for SecurePathPolicy {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SecurePathPolicy {
+    /// Create the default policy.
+    pub fn new() -> Self {
+        let euid = sys::geteuid();
+        Self {
+            trusted_uids: if euid == 0 { vec![0] } else { vec![0, euid] },
+            trusted_gids: Vec::new(),
+            allow_sticky: true,
+            allow_symlinks: false,
+        }
+    }
+
+    /// Trust files owned by `uid`.
+    pub fn with_trusted_uid(mut self, uid: u32) -> Self {
+        if !self.trusted_uids.contains(&uid) {
+            self.trusted_uids.push(uid);
+        }
+        self
+    }
+
+    /// Allow files writable by the group `gid`.
+    pub fn with_trusted_gid(mut self, gid: u32) -> Self {
+        if !self.trusted_gids.contains(&gid) {
+            self.trusted_gids.push(gid);
+        }
+        self
+    }
+
+    /// Whether group- or world-writable directories with the sticky bit are
+    /// allowed as ancestors. Entries of sticky directories can be removed or
+    /// renamed only by their owner, which is checked on the next component.
+    #[inline]
+    pub fn allow_sticky(mut self, allow: bool) -> Self {
+        self.allow_sticky = allow;
+        self
+    }
+
+    /// Whether symbolic links are followed instead of being reported.
+    ///
+    /// The owner of a followed link is still checked, and so is every
+    /// component of its target, from `/` for absolute targets.
+    #[inline]
+    pub fn allow_symlinks(mut self, allow: bool) -> Self {
+        self.allow_symlinks = allow;
+        self
+    }
+
+    /// Returns the trusted user ids.
+    #[inline]
+    pub fn trusted_uids(&self) -> &[u32] {
+        &self.trusted_uids
+    }
+
+    /// Returns the trusted group ids.
+    #[inline]
+    pub fn trusted_gids(&self) -> &[u32] {
+        &self.trusted_gids
+    }
+
+    fn issues(&self, stat: &Stat, last: bool) -> PathIssues {
+        let mode = stat.mode();
+        let sticky = self.allow_sticky && !last && stat.is_dir() && mode.svtx();
+        let mut issues = PathIssues::empty();
+
+        if !self.trusted_uids.contains(&stat.uid()) {
+            issues.insert(PathIssues::UNTRUSTED_OWNER);
+        }
+        if stat.is_link() {
+            // The mode of a symbolic link is meaningless.
+            if !self.allow_symlinks {
+                issues.insert(PathIssues::SYMLINK);
+            }
+        } else if !sticky {
+            if mode.group().contains(crate::ModePermission::WRITE)
+                && !self.trusted_gids.contains(&stat.gid())
+            {
+                issues.insert(PathIssues::GROUP_WRITABLE);
+            }
+            if mode.other().contains(crate::ModePermission::WRITE) {
+                issues.insert(PathIssues::WORLD_WRITABLE);
+            }
+        }
+        issues
+    }
+}
+
+/// A path component which violates a [SecurePathPolicy].
+#[derive(Clone, Copy)]
+pub struct PathViolation<'a> {
+    path: &'a Path,
+    stat: &'a Stat,
+    issues: PathIssues,
+}
+
+impl<'a> PathViolation<'a> {
+    /// Returns the path of the component.
+    #[inline]
+    pub fn path(&self) -> &'a Path {
+        self.path
+    }
+
+    /// Returns the metadata of the component, with its [Stat::mode] and
+    /// [Stat::uid].
+    #[inline]
+    pub fn stat(&self) -> &'a Stat {
+        self.stat
+    }
+
+    /// Returns the problems found.
+    #[inline]
+    pub fn issues(&self) -> PathIssues {
+        self.issues
+    }
+}
+
+impl core::fmt::Debug for PathViolation<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PathViolation")
+            .field("path", &self.path)
+            .field("uid", &self.stat.uid())
+            .field("gid", &self.stat.gid())
+            .field("mode", &self.stat.mode())
+            .field("issues", &self.issues)
+            .finish()
+    }
+}
+
+/// Report of [crate::validate_path], with every checked component from `/`
+/// to the file.
+#[derive(Clone)]
+pub struct SecurePathReport {
+    components: Vec<(PathBuf, Stat, PathIssues)>,
+}
+
+impl SecurePathReport {
+    /// Returns true if no component violates the policy.
+    #[inline]
+    pub fn is_secure(&self) -> bool {
+        self.violations().next().is_none()
+    }
+
+    /// Returns the checked components with their metadata.
+    pub fn components(&self) -> impl Iterator<Item = (&Path, &Stat)> {
+        self.components
+            .iter()
+            .map(|(path, stat, _)| (path.as_path(), stat))
+    }
+
+    /// Returns the components violating the policy.
+    pub fn violations(&self) -> impl Iterator<Item = PathViolation<'_>> {
+        self.components
+            .iter()
+            .filter(|(_, _, issues)| *issues != PathIssues::empty())
+            .map(|(path, stat, issues)| PathViolation {
+                path,
+                stat,
+                issues: *issues,
+            })
+    }
+}
+
+impl core::fmt::Debug for SecurePathReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.violations()).finish()
+    }
+}
+
+/// Check that the absolute `path` and all its ancestors are owned by a
+/// trusted user and not writable by others, like ssh `StrictModes`.
+///
+/// Components are opened one by one with `openat(O_PATH | O_NOFOLLOW)`
+/// relative to their parent and checked with [crate::fstat], so every
+/// checked component is the one actually traversed. `..` moves back to the
+/// parent directory. The walk stops at the first symbolic link unless
+/// allowed by the policy, in which case the link is resolved with
+/// `readlinkat` and every component of its target is checked too.
+///
+/// Returns [Errno::EINVAL] if `path` is relative and [Errno::ELOOP] if too
+/// many symbolic links are followed.
+pub fn validate_path<P: AsRef<Path>>(
+    path: P,
+    policy: &SecurePathPolicy,
+) -> Result<SecurePathReport, Errno> {
+    let path = path.as_ref();
+    if !path.is_absolute() {
+        return Err(Errno::EINVAL);
+    }
+
+    let mut names = Vec::new();
+    push_names(&mut names, path);
+
+    let mut report = SecurePathReport {
+        components: Vec::with_capacity(names.len() + 1),
+    };
+    let mut current = PathBuf::from("/");
+    let mut dir = open_root()?;
+    let stat = unsafe { fstat(dir.as_raw_fd())? };
+    let issues = policy.issues(&stat, names.is_empty());
+    report.components.push((current.clone(), stat, issues));
+
+    let mut target = vec![0u8; PATH_MAX];
+    let mut links = 0;
+    while let Some(name) = names.pop() {
+        let cname = StackPath::<{ NAME_MAX + 1 }>::new(&name)?;
+        let fd = unsafe {
+            sys::openat(
+                dir.as_raw_fd(),
+                cname.as_cstr(),
+                O_PATH | O_NOFOLLOW | O_CLOEXEC,
+                0,
+            )?
+        };
+        let stat = unsafe { fstat(fd.as_raw_fd())? };
+        if name == b".." {
+            current.pop();
+        } else {
+            current.push(OsStr::from_bytes(&name));
+        }
+        let issues = policy.issues(&stat, names.is_empty());
+        report.components.push((current.clone(), stat, issues));
+
+        if stat.is_link() {
+            if !policy.allow_symlinks {
+                break;
+            }
+            links += 1;
+            if links > sys::MAXSYMLINKS {
+                return Err(Errno::ELOOP);
+            }
+
+            let len = unsafe { sys::readlinkat(dir.as_raw_fd(), cname.as_cstr(), &mut target)? };
+            if len == target.len() {
+                return Err(Errno::ENAMETOOLONG);
+            }
+            let target = Path::new(OsStr::from_bytes(&target[..len]));
+            push_names(&mut names, target);
+            current.pop();
+            if target.is_absolute() {
+                current = PathBuf::from("/");
+                dir = open_root()?;
+            }
+            continue;
+        }
+        dir = fd;
+    }
+
+    Ok(report)
+}
+
+/// Push the components of `path` on the stack `names`, the first component
+/// on top.
+fn push_names(names: &mut Vec<Vec<u8>>, path: &Path) {
+    let start = names.len();
+    names.extend(path.components().filter_map(|c| match c {
+        Component::Normal(name) => Some(name.as_bytes().to_vec()),
+        Component::ParentDir => Some(b"..".to_vec()),
+        _ => None,
+    }));
+    names[start..].reverse();
+}
+
+#[inline]
+fn open_root() -> Result<sys::Fd, Errno> {
+    unsafe {
+        sys::openat(
+            CURRENT_DIRECTORY,
+            crate::CStr::from_bytes_with_nul_unchecked(b"/\0"),
+            O_PATH | O_CLOEXEC,
+            0,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate() {
+        linux_syscalls::init();
+
+        assert_eq!(
+            validate_path("relative", &SecurePathPolicy::new()).map(|_| ()),
+            Err(Errno::EINVAL)
+        );
+
+        let report = validate_path("/dev/null", &SecurePathPolicy::new()).unwrap();
+        let violations = report.violations().collect::<Vec<_>>();
+        assert_eq!(report.components().count(), 3);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].path(), Path::new("/dev/null"));
+        assert!(violations[0].issues().contains(PathIssues::WORLD_WRITABLE));
+
+        let dir = crate::tests::TempDir::new("secure");
+        std::fs::set_permissions(&dir, std::os::unix::fs::PermissionsExt::from_mode(0o755))
+            .unwrap();
+        let file = dir.join("file");
+        let link = dir.join("link");
+        std::fs::write(&file, b"").unwrap();
+        std::os::unix::fs::symlink(&file, &link).unwrap();
+        std::fs::create_dir(dir.join("sub")).unwrap();
+
+        std::fs::set_permissions(&file, std::os::unix::fs::PermissionsExt::from_mode(0o600))
+            .unwrap();
+        let policy = SecurePathPolicy::new();
+        // The temporary directory may be a sticky /tmp.
+        let secure = validate_path(&file, &policy).unwrap().is_secure();
+        let tmp = crate::stat(std::env::temp_dir()).unwrap();
+        if tmp.mode().svtx() || !tmp.mode().other().contains(crate::ModePermission::WRITE) {
+            assert!(secure);
+        }
+
+        std::fs::set_permissions(&file, std::os::unix::fs::PermissionsExt::from_mode(0o620))
+            .unwrap();
+        let report = validate_path(&file, &policy).unwrap();
+        let last = report.violations().last().unwrap();
+        assert_eq!(last.path(), file);
+        assert_eq!(last.issues(), PathIssues::GROUP_WRITABLE);
+        assert_eq!(last.stat().mode().as_u16(), 0o620);
+        let gid = last.stat().gid();
+        let trusted = policy.clone().with_trusted_gid(gid);
+        assert!(!validate_path(&file, &trusted)
+            .unwrap()
+            .violations()
+            .any(|v| v.path() == file));
+
+        let report = validate_path(&link, &policy).unwrap();
+        let last = report.violations().last().unwrap();
+        assert_eq!(last.path(), link);
+        assert!(last.issues().contains(PathIssues::SYMLINK));
+        let report = validate_path(&link, &policy.clone().allow_symlinks(true)).unwrap();
+        assert_eq!(
+            report
+                .components()
+                .last()
+                .map(|(_, stat)| stat.mode().as_u16()),
+            Some(0o620)
+        );
+
+        let report = validate_path(dir.join("sub/../file"), &policy).unwrap();
+        assert_eq!(
+            report.components().last().map(|(path, _)| path),
+            Some(&*file)
+        );
+
+        // The directories leading to the target of a followed link are
+        // checked too.
+        let open = dir.join("open");
+        std::fs::create_dir(&open).unwrap();
+        std::fs::set_permissions(&open, std::os::unix::fs::PermissionsExt::from_mode(0o777))
+            .unwrap();
+        std::fs::write(open.join("target"), b"").unwrap();
+        std::os::unix::fs::symlink("open/target", dir.join("via")).unwrap();
+        let report = validate_path(dir.join("via"), &policy.clone().allow_symlinks(true)).unwrap();
+        assert!(report
+            .violations()
+            .any(|v| v.path() == open && v.issues().contains(PathIssues::WORLD_WRITABLE)));
+        assert_eq!(
+            report.components().last().map(|(path, _)| path),
+            Some(&*open.join("target"))
+        );
+
+        if tmp.mode().svtx() {
+            let report = validate_path(&file, &policy.allow_sticky(false)).unwrap();
+            assert!(report
+                .violations()
+                .any(|v| v.stat().is_dir() && v.stat().mode().svtx()));
+        }
+    }
+}
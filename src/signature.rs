@@ -0,0 +1,317 @@
+use linux_syscalls::bitflags;
+
+use crate::{Dev, Stat, Timestamp, TimestampGranularity};
+
+bitflags! {
+    /// Fields compared by [StatSignature::matches].
+    #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub enum SignatureFields: u8 {
+        /// Last status change timestamp.
+        CTIME = 0x01,
+        /// Last modification timestamp.
+        MTIME = 0x02,
+        /// Inode number.
+        INODE = 0x04,
+        /// Device on which the file resides.
+        DEV = 0x08,
+        /// User ID of the owner.
+        UID = 0x10,
+        /// Group ID of the owner.
+        GID = 0x20,
+        /// Size in bytes.
+        SIZE = 0x40,
+    }
+}
+
+/// Snapshot of the stat data used to detect file modifications, in the
+/// style of git's index entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StatSignature {
+    ctime: Timestamp,
+    mtime: Timestamp,
+    inode: u64,
+    dev: Dev,
+    uid: u32,
+    gid: u32,
+    size: i64,
+}
+
+impl StatSignature {
+    /// Create a new [StatSignature] from a [Stat].
+    #[inline]
+    pub const fn from_stat(stat: &Stat) -> Self {
+        Self {
+            ctime: stat.ctime(),
+            mtime: stat.mtime(),
+            inode: stat.inode(),
+            dev: stat.dev(),
+            uid: stat.uid(),
+            gid: stat.gid(),
+            size: stat.size(),
+        }
+    }
+
+    /// Returns the file's last status change timestamp.
+    #[inline]
+    pub const fn ctime(&self) -> Timestamp {
+        self.ctime
+    }
+
+    /// Returns the file's last modification timestamp.
+    #[inline]
+    pub const fn mtime(&self) -> Timestamp {
+        self.mtime
+    }
+
+    /// Returns the inode number of the file.
+    #[inline]
+    pub const fn inode(&self) -> u64 {
+        self.inode
+    }
+
+    /// Returns the device on which the file resides.
+    #[inline]
+    pub const fn dev(&self) -> Dev {
+        self.dev
+    }
+
+    /// Returns the user ID of the owner of the file.
+    #[inline]
+    pub const fn uid(&self) -> u32 {
+        self.uid
+    }
+
+    /// Returns the ID of the group owner of the file.
+    #[inline]
+    pub const fn gid(&self) -> u32 {
+        self.gid
+    }
+
+    /// Returns the size of the file in bytes.
+    #[inline]
+    pub const fn size(&self) -> i64 {
+        self.size
+    }
+
+    /// Returns the set of `fields` that differ between the two signatures.
+    pub fn changed(&self, other: &Self, fields: SignatureFields) -> SignatureFields {
+        let mut changed = SignatureFields::empty();
+        if fields.contains(SignatureFields::CTIME) && self.ctime != other.ctime {
+            changed.insert(SignatureFields::CTIME);
+        }
+        if fields.contains(SignatureFields::MTIME) && self.mtime != other.mtime {
+            changed.insert(SignatureFields::MTIME);
+        }
+        if fields.contains(SignatureFields::INODE) && self.inode != other.inode {
+            changed.insert(SignatureFields::INODE);
+        }
+        if fields.contains(SignatureFields::DEV) && self.dev != other.dev {
+            changed.insert(SignatureFields::DEV);
+        }
+        if fields.contains(SignatureFields::UID) && self.uid != other.uid {
+            changed.insert(SignatureFields::UID);
+        }
+        if fields.contains(SignatureFields::GID) && self.gid != other.gid {
+            changed.insert(SignatureFields::GID);
+        }
+        if fields.contains(SignatureFields::SIZE) && self.size != other.size {
+            changed.insert(SignatureFields::SIZE);
+        }
+        changed
+    }
+
+    /// Returns true if all the `fields` are equal in the two signatures.
+    #[inline]
+    pub fn matches(&self, other: &Self, fields: SignatureFields) -> bool {
+        self.changed(other, fields) == SignatureFields::empty()
+    }
+
+    /// Returns true if the file could have been modified in the same
+    /// timestamp tick the `snapshot` was taken, that is if `mtime` is not
+    /// strictly older than `snapshot`.
+    ///
+    /// A racily clean entry cannot be trusted to be unchanged even if its
+    /// signature matches and the content must be compared.
+    #[inline]
+    pub fn is_racily_clean(&self, snapshot: Timestamp) -> bool {
+        self.mtime >= snapshot
+    }
+
+    /// Like [StatSignature::is_racily_clean] but the timestamps are compared
+    /// with the resolution of the filesystem (see
+    /// [crate::timestamp_granularity]).
+    #[inline]
+    pub fn is_racily_clean_with(
+        &self,
+        snapshot: Timestamp,
+        granularity: TimestampGranularity,
+    ) -> bool {
+        self.mtime >= snapshot || granularity.same(self.mtime, snapshot)
+    }
+}
+
+impl From<&Stat> for StatSignature {
+    #[inline]
+    fn from(value: &Stat) -> Self {
+        Self::from_stat(value)
+    }
+}
+
+impl From<Stat> for StatSignature {
+    #[inline]
+    fn from(value: Stat) -> Self {
+        Self::from_stat(&value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn racily_clean() {
+        linux_syscalls::init();
+
+        let stat = crate::retry(|| crate::stat(crate::tests::dev_null())).unwrap();
+        let sig = StatSignature::from_stat(&stat);
+        assert!(sig.matches(&sig, SignatureFields::all()));
+        assert_eq!(
+            sig.changed(&sig, SignatureFields::all()),
+            SignatureFields::empty()
+        );
+
+        let mtime = sig.mtime();
+        assert!(sig.is_racily_clean(mtime));
+        assert!(sig.is_racily_clean(Timestamp::new(mtime.secs() - 1, 0)));
+        assert!(!sig.is_racily_clean(Timestamp::new(mtime.secs() + 1, 0)));
+        assert!(sig.is_racily_clean_with(
+            Timestamp::new(mtime.secs(), 999_999_999),
+            TimestampGranularity::Seconds
+        ));
+        assert!(!sig.is_racily_clean_with(
+            Timestamp::new(mtime.secs() + 1, 0),
+            TimestampGranularity::Seconds
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn changed_fields() {
+        use crate::{retry, StatAtFlags, CURRENT_DIRECTORY};
+
+        linux_syscalls::init();
+
+        let dir = crate::tests::TempDir::new("signature");
+        let file = dir.join("file");
+        let other = dir.join("other");
+        std::fs::write(&file, b"content").unwrap();
+        std::fs::write(&other, b"content").unwrap();
+        let set_mtime = |path: &std::path::Path, secs: i64| {
+            let time = Timestamp::new(secs, 0);
+            retry(|| unsafe {
+                crate::utimensat(
+                    CURRENT_DIRECTORY,
+                    path,
+                    Some(time),
+                    Some(time),
+                    StatAtFlags::empty(),
+                )
+            })
+            .unwrap();
+        };
+        set_mtime(&file, 1_000_000_000);
+        set_mtime(&other, 1_000_000_000);
+        let signature = |path: &std::path::Path| {
+            StatSignature::from_stat(&retry(|| crate::stat(path)).unwrap())
+        };
+        let all_but = |field: SignatureFields| {
+            let mut fields = SignatureFields::all();
+            fields.remove(field);
+            fields
+        };
+
+        // The same file before and after a write.
+        let before = signature(&file);
+        std::fs::write(&file, b"new content").unwrap();
+        set_mtime(&file, 1_500_000_000);
+        let after = signature(&file);
+
+        let ctime = if before.ctime() != after.ctime() {
+            SignatureFields::CTIME
+        } else {
+            SignatureFields::empty()
+        };
+        assert_eq!(
+            before.changed(&after, SignatureFields::all()),
+            SignatureFields::MTIME | SignatureFields::SIZE | ctime
+        );
+        assert!(!before.matches(&after, SignatureFields::all()));
+        assert_eq!(
+            before.changed(&after, SignatureFields::MTIME | SignatureFields::INODE),
+            SignatureFields::MTIME
+        );
+        let identity = SignatureFields::INODE
+            | SignatureFields::DEV
+            | SignatureFields::UID
+            | SignatureFields::GID;
+        assert_eq!(before.changed(&after, identity), SignatureFields::empty());
+        assert!(before.matches(&after, identity));
+
+        // Two files with the same content and times.
+        let other = signature(&other);
+        assert_eq!(
+            before.changed(&other, all_but(SignatureFields::CTIME)),
+            SignatureFields::INODE
+        );
+        assert!(before.matches(
+            &other,
+            SignatureFields::MTIME | SignatureFields::SIZE | SignatureFields::DEV
+        ));
+
+        // Each field on its own.
+        let time = Timestamp::new(1, 0);
+        for (field, changed) in [
+            (
+                SignatureFields::CTIME,
+                StatSignature {
+                    ctime: time,
+                    ..before
+                },
+            ),
+            (
+                SignatureFields::MTIME,
+                StatSignature {
+                    mtime: time,
+                    ..before
+                },
+            ),
+            (SignatureFields::INODE, StatSignature { inode: 1, ..before }),
+            (
+                SignatureFields::DEV,
+                StatSignature {
+                    dev: Dev::from_split(1, 1),
+                    ..before
+                },
+            ),
+            (
+                SignatureFields::UID,
+                StatSignature {
+                    uid: !before.uid,
+                    ..before
+                },
+            ),
+            (
+                SignatureFields::GID,
+                StatSignature {
+                    gid: !before.gid,
+                    ..before
+                },
+            ),
+            (SignatureFields::SIZE, StatSignature { size: -1, ..before }),
+        ] {
+            assert_eq!(before.changed(&changed, SignatureFields::all()), field);
+            assert_eq!(before.changed(&changed, field), field);
+            assert!(before.matches(&changed, all_but(field)));
+        }
+    }
+}
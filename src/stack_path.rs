@@ -0,0 +1,200 @@
+use core::convert::TryFrom;
+
+use linux_syscalls::Errno;
+
+use crate::CStr;
+
+/// Default size of the [StackPath] buffer, NUL terminator included.
+pub const STACK_PATH_DEFAULT: usize = 384;
+
+/// Path copied into a NUL terminated buffer of `N` bytes on the stack, to
+/// call the functions of this crate with a `&[u8]` or `&str` path without
+/// heap allocation (e.g. without `std`, where [crate::Path] is [CStr]).
+/// It can be built with [TryFrom] or [str::parse].
+#[derive(Clone, Copy)]
+pub struct StackPath<const N: usize = STACK_PATH_DEFAULT> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> StackPath<N> {
+    /// Copy `path` into the buffer. A trailing NUL byte is accepted.
+    ///
+    /// Returns [Errno::ENAMETOOLONG] if `path` and its terminator do not fit
+    /// in `N` bytes and [Errno::EINVAL] if it contains other NUL bytes.
+    pub fn new(path: &[u8]) -> Result<Self, Errno> {
+        let path = match path.split_last() {
+            Some((0, path)) => path,
+            _ => path,
+        };
+        if path.len() >= N {
+            return Err(Errno::ENAMETOOLONG);
+        }
+        if path.contains(&0) {
+            return Err(Errno::EINVAL);
+        }
+
+        let mut buf = [0u8; N];
+        buf[..path.len()].copy_from_slice(path);
+        Ok(Self {
+            buf,
+            len: path.len(),
+        })
+    }
+
+    /// Returns the path without the NUL terminator.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Returns the path as a [CStr].
+    #[inline]
+    pub fn as_cstr(&self) -> &CStr {
+        unsafe { CStr::from_bytes_with_nul_unchecked(&self.buf[..=self.len]) }
+    }
+
+    /// Returns the length of the path without the NUL terminator.
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the path is empty.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// Call `f` with `path` as a [CStr] copied into a [StackPath] of `N` bytes.
+///
+/// Without the `alloc` feature paths which do not fit fail with
+/// [Errno::ENAMETOOLONG], with it they are copied into a `CString`.
+pub fn run_with_bytes<const N: usize, T, F>(path: &[u8], f: F) -> Result<T, Errno>
+where
+    F: FnOnce(&CStr) -> Result<T, Errno>,
+{
+    match StackPath::<N>::new(path) {
+        Ok(path) => f(path.as_cstr()),
+        #[cfg(feature = "alloc")]
+        Err(Errno::ENAMETOOLONG) => {
+            let path = match path.split_last() {
+                Some((0, path)) => path,
+                _ => path,
+            };
+            crate::CString::new(path).map_or(Err(Errno::EINVAL), |path| f(&path))
+        }
+        Err(err) => Err(err),
+    }
+}
+
+impl<const N: usize> TryFrom<&[u8]> for StackPath<N> {
+    type Error = Errno;
+
+    #[inline]
+    fn try_from(path: &[u8]) -> Result<Self, Self::Error> {
+        Self::new(path)
+    }
+}
+
+impl<const N: usize> TryFrom<&str> for StackPath<N> {
+    type Error = Errno;
+
+    #[inline]
+    fn try_from(path: &str) -> Result<Self, Self::Error> {
+        Self::new(path.as_bytes())
+    }
+}
+
+impl<const N: usize> core::str::FromStr for StackPath<N> {
+    type Err = Errno;
+
+    #[inline]
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        Self::new(path.as_bytes())
+    }
+}
+
+impl<const N: usize> AsRef<CStr> for StackPath<N> {
+    #[inline]
+    fn as_ref(&self) -> &CStr {
+        self.as_cstr()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> AsRef<std::path::Path> for StackPath<N> {
+    #[inline]
+    fn as_ref(&self) -> &std::path::Path {
+        use std::os::unix::ffi::OsStrExt;
+
+        std::path::Path::new(std::ffi::OsStr::from_bytes(self.as_bytes()))
+    }
+}
+
+impl<const N: usize> core::fmt::Debug for StackPath<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Debug::fmt(self.as_cstr(), f)
+    }
+}
+
+impl<const N: usize> PartialEq for StackPath<N> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl<const N: usize> Eq for StackPath<N> {}
+
+impl<const N: usize> core::hash::Hash for StackPath<N> {
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn stack_path() {
+        linux_syscalls::init();
+
+        let path = StackPath::<10>::from_str("/dev/null").unwrap();
+        assert_eq!(path.as_bytes(), b"/dev/null");
+        assert_eq!(path.as_cstr().to_bytes_with_nul(), b"/dev/null\0");
+        assert_eq!(StackPath::<10>::new(b"/dev/null\0"), Ok(path));
+        assert_eq!(
+            StackPath::<9>::from_str("/dev/null").map(|_| ()),
+            Err(Errno::ENAMETOOLONG)
+        );
+        assert_eq!(
+            StackPath::<10>::new(b"/dev\0null").map(|_| ()),
+            Err(Errno::EINVAL)
+        );
+        assert!(StackPath::<1>::new(b"").unwrap().is_empty());
+        assert_eq!(
+            StackPath::<0>::new(b"").map(|_| ()),
+            Err(Errno::ENAMETOOLONG)
+        );
+
+        assert!(crate::retry(|| crate::stat(path)).unwrap().is_char());
+        assert!(
+            crate::retry(|| run_with_bytes::<16, _, _>(b"/dev/null", crate::stat_cstr))
+                .unwrap()
+                .is_char()
+        );
+        let long = run_with_bytes::<4, _, _>(b"/dev/null", crate::stat_cstr);
+        #[cfg(feature = "alloc")]
+        assert!(long.unwrap().is_char());
+        #[cfg(not(feature = "alloc"))]
+        assert_eq!(long.map(|_| ()), Err(Errno::ENAMETOOLONG));
+        let path: StackPath = "/dev/null".parse().unwrap();
+        assert!(crate::retry(|| crate::lstat(path)).unwrap().is_char());
+    }
+}
@@ -0,0 +1,94 @@
+use linux_syscalls::{bitflags, Errno};
+
+use crate::{run_with_cstr, CStr, Path, RawFd};
+
+bitflags! {
+    /// Mount flags of a filesystem returned by [crate::statfs].
+    #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub enum MountFlags: u32 {
+        /// Read-only filesystem.
+        RDONLY = 0x0001,
+        /// Set-user-ID and set-group-ID bits are ignored by exec(3).
+        NOSUID = 0x0002,
+        /// Disallow access to device special files on this filesystem.
+        NODEV = 0x0004,
+        /// Execution of programs is disallowed on this filesystem.
+        NOEXEC = 0x0008,
+        /// Writes are synched to the filesystem immediately.
+        SYNCHRONOUS = 0x0010,
+        /// The other flags are valid (always set since Linux 2.6.36).
+        VALID = 0x0020,
+        /// Mandatory locking is permitted on the filesystem.
+        MANDLOCK = 0x0040,
+        /// Do not update access times.
+        NOATIME = 0x0400,
+        /// Do not update directory access times.
+        NODIRATIME = 0x0800,
+        /// Update access times relative to modification or change times.
+        RELATIME = 0x1000,
+        /// Symbolic links are not followed when resolving paths.
+        NOSYMFOLLOW = 0x2000,
+    }
+}
+
+/// Filesystem informations returned by [crate::statfs].
+pub type StatFs = crate::raw::statfs64;
+
+/// Returns informations about the filesystem containing `path`.
+#[inline]
+pub fn statfs<P: AsRef<Path>>(path: P) -> Result<StatFs, Errno> {
+    run_with_cstr(path, statfs_cstr)
+}
+
+/// Returns informations about the filesystem containing `path`.
+/// Accept `path` as a [CStr].
+#[inline]
+pub fn statfs_cstr(path: &CStr) -> Result<StatFs, Errno> {
+    crate::retry(|| crate::raw::statfs_cstr(path))
+}
+
+/// Returns informations about the filesystem containing the open file `fd`.
+///
+/// # Safety
+///
+/// This function is marked as unsafe because file descriptor (`fd`) cannot
+/// be checked.
+#[inline]
+pub unsafe fn fstatfs(fd: RawFd) -> Result<StatFs, Errno> {
+    crate::retry(|| crate::raw::fstatfs(fd))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{retry, tests::dev_null, CURRENT_DIRECTORY};
+
+    #[test]
+    fn statfs_and_fstatfs() {
+        linux_syscalls::init();
+
+        let by_path = statfs(dev_null()).unwrap();
+        assert!(by_path.flags().contains(MountFlags::VALID));
+        assert!(by_path.block_size() > 0);
+        assert!(by_path.name_max() > 0);
+
+        let fd = retry(|| unsafe {
+            run_with_cstr(dev_null(), |path| {
+                crate::sys::openat(
+                    CURRENT_DIRECTORY,
+                    path,
+                    linux_raw_sys::general::O_PATH | linux_raw_sys::general::O_CLOEXEC,
+                    0,
+                )
+            })
+        })
+        .unwrap();
+        let by_fd = unsafe { fstatfs(fd.as_raw_fd()) }.unwrap();
+        assert_eq!(by_fd.fs_type(), by_path.fs_type());
+        assert_eq!(by_fd.fsid(), by_path.fsid());
+        assert_eq!(by_fd.flags(), by_path.flags());
+
+        assert_eq!(unsafe { fstatfs(-1) }.map(|_| ()), Err(Errno::EBADF));
+        assert_eq!(statfs(crate::empty_path()).map(|_| ()), Err(Errno::ENOENT));
+    }
+}
@@ -0,0 +1,247 @@
+//! Thin syscall helpers used internally.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use linux_raw_sys::general::{open_how, O_LARGEFILE, O_PATH};
+use linux_syscalls::{syscall, Errno, Sysno};
+
+use crate::{CStr, RawFd};
+
+/// Owned file descriptor, closed on drop.
+pub(crate) struct Fd(RawFd);
+
+impl Fd {
+    #[inline]
+    pub(crate) const fn as_raw_fd(&self) -> RawFd {
+        self.0
+    }
+
+    /// Release ownership of the file descriptor.
+    #[inline]
+    pub(crate) fn into_raw_fd(self) -> RawFd {
+        let fd = self.0;
+        core::mem::forget(self);
+        fd
+    }
+}
+
+impl Drop for Fd {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { close(self.0) };
+    }
+}
+
+/// Invoke `openat` system call, the returned file descriptor is owned.
+///
+/// # Safety
+///
+/// Directory file descriptor (`dirfd`) cannot be checked.
+#[inline]
+pub(crate) unsafe fn openat(dirfd: RawFd, path: &CStr, flags: u32, mode: u32) -> Result<Fd, Errno> {
+    crate::retry(|| {
+        syscall!(
+            Sysno::openat,
+            dirfd,
+            path.as_ptr(),
+            flags | O_LARGEFILE,
+            mode
+        )
+    })
+    .map(|fd| Fd(fd as RawFd))
+}
+
+static HAS_OPENAT2: AtomicBool = AtomicBool::new(true);
+
+/// Invoke `openat2` system call (Linux 5.6) with the `RESOLVE_*` flags
+/// `resolve`, the returned file descriptor is owned.
+///
+/// Returns [Errno::ENOSYS] without trying again once the kernel reported it
+/// as not available.
+///
+/// # Safety
+///
+/// Directory file descriptor (`dirfd`) cannot be checked.
+pub(crate) unsafe fn openat2(
+    dirfd: RawFd,
+    path: &CStr,
+    flags: u32,
+    resolve: u32,
+) -> Result<Fd, Errno> {
+    if !HAS_OPENAT2.load(Ordering::Relaxed) {
+        return Err(Errno::ENOSYS);
+    }
+
+    // O_PATH accepts only a few other flags with openat2.
+    let flags = if flags & O_PATH == 0 {
+        flags | O_LARGEFILE
+    } else {
+        flags
+    };
+    let how = open_how {
+        flags: flags as u64,
+        mode: 0,
+        resolve: resolve as u64,
+    };
+    match crate::retry(|| {
+        syscall!(
+            Sysno::openat2,
+            dirfd,
+            path.as_ptr(),
+            &how as *const open_how,
+            core::mem::size_of::<open_how>()
+        )
+    }) {
+        Err(Errno::ENOSYS) => {
+            HAS_OPENAT2.store(false, Ordering::Relaxed);
+            Err(Errno::ENOSYS)
+        }
+        other => other.map(|fd| Fd(fd as RawFd)),
+    }
+}
+
+/// Invoke `close` system call ignoring errors.
+///
+/// # Safety
+///
+/// File descriptor (`fd`) cannot be checked.
+#[inline]
+pub(crate) unsafe fn close(fd: RawFd) {
+    let _ = syscall!(Sysno::close, fd);
+}
+
+/// Invoke `unlinkat` system call.
+///
+/// # Safety
+///
+/// Directory file descriptor (`dirfd`) cannot be checked.
+#[inline]
+pub(crate) unsafe fn unlinkat(dirfd: RawFd, path: &CStr, flags: u32) -> Result<(), Errno> {
+    crate::retry(|| syscall!(Sysno::unlinkat, dirfd, path.as_ptr(), flags)).map(|_| ())
+}
+
+/// Invoke `getpid` system call.
+#[inline]
+pub(crate) fn getpid() -> u32 {
+    unsafe { syscall!([ro] Sysno::getpid) }.unwrap_or(0) as u32
+}
+
+/// Writes `prefix` followed by `index` in decimal and a NUL into `buf`.
+pub(crate) fn format_path(
+    buf: &mut [u8],
+    prefix: &str,
+    index: Option<u32>,
+) -> Result<usize, Errno> {
+    let mut digits = [0u8; 10];
+    let mut n_digits = 0;
+    if let Some(mut index) = index {
+        loop {
+            digits[n_digits] = b'0' + (index % 10) as u8;
+            n_digits += 1;
+            index /= 10;
+            if index == 0 {
+                break;
+            }
+        }
+    }
+
+    let len = prefix.len() + n_digits;
+    if len >= buf.len() {
+        return Err(Errno::ERANGE);
+    }
+    buf[..prefix.len()].copy_from_slice(prefix.as_bytes());
+    for (dst, src) in buf[prefix.len()..len]
+        .iter_mut()
+        .zip(digits[..n_digits].iter().rev())
+    {
+        *dst = *src;
+    }
+    buf[len] = 0;
+    Ok(len)
+}
+
+/// Writes `/proc/self/fd/<fd>` into `buf`, the path reopens (or operates
+/// on) the file referred by `fd`, even if `fd` was opened with `O_PATH`.
+pub(crate) fn proc_self_fd(fd: RawFd, buf: &mut [u8; 32]) -> &CStr {
+    // Always fits: 14 bytes of prefix, at most 10 digits and the NUL.
+    let len =
+        format_path(buf, "/proc/self/fd/", Some(fd as u32)).unwrap_or_else(|_| unreachable!());
+    unsafe { CStr::from_bytes_with_nul_unchecked(&buf[..=len]) }
+}
+
+macro_rules! id_syscall {
+    ($(#[$meta:meta])* $name:ident, $sysno:ident, $sysno32:ident) => {
+        $(#[$meta])*
+        #[inline]
+        pub(crate) fn $name() -> u32 {
+            #[cfg(any(target_arch = "x86", target_arch = "arm"))]
+            let sysno = Sysno::$sysno32;
+            #[cfg(not(any(target_arch = "x86", target_arch = "arm")))]
+            let sysno = Sysno::$sysno;
+            unsafe { syscall!([ro] sysno) }.unwrap_or(0) as u32
+        }
+    };
+}
+
+id_syscall!(
+    /// Invoke `getuid` system call (`getuid32` where ids are 16 bits).
+    getuid, getuid, getuid32
+);
+id_syscall!(
+    /// Invoke `geteuid` system call (`geteuid32` where ids are 16 bits).
+    geteuid, geteuid, geteuid32
+);
+id_syscall!(
+    /// Invoke `getgid` system call (`getgid32` where ids are 16 bits).
+    getgid, getgid, getgid32
+);
+id_syscall!(
+    /// Invoke `getegid` system call (`getegid32` where ids are 16 bits).
+    getegid, getegid, getegid32
+);
+
+/// Maximum number of symbolic links followed during a path walk, like the
+/// kernel.
+pub(crate) const MAXSYMLINKS: usize = 40;
+
+/// Invoke `readlinkat` system call, returns the length of the target written
+/// into `buf` (not NUL terminated).
+///
+/// # Safety
+///
+/// Directory file descriptor (`dirfd`) cannot be checked.
+#[inline]
+pub(crate) unsafe fn readlinkat(dirfd: RawFd, path: &CStr, buf: &mut [u8]) -> Result<usize, Errno> {
+    syscall!(
+        Sysno::readlinkat,
+        dirfd,
+        path.as_ptr(),
+        buf.as_mut_ptr(),
+        buf.len()
+    )
+}
+
+/// Returns the mount ID of `path` relative to `dirfd` with
+/// `name_to_handle_at` (Linux 2.6.39), or `None` if the filesystem does not
+/// support file handles (e.g. proc).
+///
+/// # Safety
+///
+/// Directory file descriptor (`dirfd`) cannot be checked.
+pub(crate) unsafe fn name_to_mount_id(dirfd: RawFd, path: &CStr, flags: u32) -> Option<u64> {
+    // `struct file_handle` with an empty `f_handle`, the mount ID is
+    // reported even if the handle does not fit.
+    let mut handle = [0u32; 2];
+    let mut mount_id = 0i32;
+    match syscall!(
+        Sysno::name_to_handle_at,
+        dirfd,
+        path.as_ptr(),
+        handle.as_mut_ptr(),
+        &mut mount_id as *mut i32,
+        flags
+    ) {
+        Ok(_) | Err(Errno::EOVERFLOW) => Some(mount_id as u64),
+        Err(_) => None,
+    }
+}
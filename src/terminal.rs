@@ -0,0 +1,130 @@
+use linux_syscalls::Errno;
+
+use crate::{fstat, stat_cstr, sys::format_path, CStr, Dev, FileType, RawFd};
+
+/// Returns true if the file descriptor `fd` refers to a terminal.
+///
+/// Unlike `isatty` no ioctl is issued: `fd` is checked with [crate::fstat]
+/// to be a character device with a terminal major (see
+/// [crate::DeviceKind::is_terminal]).
+///
+/// # Safety
+///
+/// This function is marked as unsafe because file descriptor (`fd`) cannot
+/// be checked.
+#[inline]
+pub unsafe fn is_terminal(fd: RawFd) -> bool {
+    terminal_dev(fd).is_ok()
+}
+
+unsafe fn terminal_dev(fd: RawFd) -> Result<Dev, Errno> {
+    let stat = fstat(fd)?;
+    if stat.is_char()
+        && stat
+            .rdev()
+            .describe_static(FileType::Character)
+            .map_or(false, |kind| kind.is_terminal())
+    {
+        Ok(stat.rdev())
+    } else {
+        Err(Errno::ENOTTY)
+    }
+}
+
+/// Returns the `/dev` path candidate for a terminal device as a prefix and an
+/// optional index.
+fn candidate(dev: Dev) -> Option<(&'static str, Option<u32>)> {
+    let (major, minor) = (dev.major(), dev.minor());
+    Some(match major {
+        4 if minor < 64 => ("/dev/tty", Some(minor)),
+        4 if minor < 256 => ("/dev/ttyS", Some(minor - 64)),
+        5 if minor == 0 => ("/dev/tty", None),
+        5 if minor == 1 => ("/dev/console", None),
+        136..=143 => ("/dev/pts/", Some(((major - 136) << 20) | minor)),
+        166 => ("/dev/ttyACM", Some(minor)),
+        188 => ("/dev/ttyUSB", Some(minor)),
+        _ => return None,
+    })
+}
+
+/// Returns the path of the terminal referred by the file descriptor `fd`
+/// (e.g. `/dev/pts/3` or `/dev/tty1`), like `ttyname_r`, written into
+/// `buf`.
+///
+/// The path is derived from the device major and minor and verified with
+/// [crate::stat_cstr], no directory is scanned.
+/// Returns [Errno::ENOTTY] if `fd` is not a terminal, [Errno::ERANGE] if
+/// `buf` is too small and [Errno::ENOENT] if the device node is not where
+/// expected.
+///
+/// # Safety
+///
+/// This function is marked as unsafe because file descriptor (`fd`) cannot
+/// be checked.
+pub unsafe fn terminal_name(fd: RawFd, buf: &mut [u8]) -> Result<&CStr, Errno> {
+    let dev = terminal_dev(fd)?;
+    let (prefix, index) = candidate(dev).ok_or(Errno::ENOENT)?;
+    let len = format_path(buf, prefix, index)?;
+    let path = CStr::from_bytes_with_nul_unchecked(&buf[..=len]);
+
+    match stat_cstr(path) {
+        Ok(stat) if stat.is_char() && stat.rdev() == dev => Ok(path),
+        Ok(_) => Err(Errno::ENOENT),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn candidates() {
+        let check = |major, minor, expected: &str| {
+            let mut buf = [0u8; 32];
+            let (prefix, index) = candidate(Dev::from_split(major, minor)).unwrap();
+            let len = format_path(&mut buf, prefix, index).unwrap();
+            assert_eq!(&buf[..=len], expected.as_bytes());
+        };
+
+        check(4, 1, "/dev/tty1\0");
+        check(4, 0, "/dev/tty0\0");
+        check(4, 65, "/dev/ttyS1\0");
+        check(5, 0, "/dev/tty\0");
+        check(5, 1, "/dev/console\0");
+        check(136, 0, "/dev/pts/0\0");
+        check(136, 1234, "/dev/pts/1234\0");
+        check(137, 2, "/dev/pts/1048578\0");
+        check(188, 0, "/dev/ttyUSB0\0");
+        assert!(candidate(Dev::from_split(1, 3)).is_none());
+
+        assert_eq!(
+            format_path(&mut [0u8; 9], "/dev/tty", Some(1)),
+            Err(Errno::ERANGE)
+        );
+    }
+
+    #[test]
+    fn not_a_terminal() {
+        linux_syscalls::init();
+
+        let mut buf = [0u8; 32];
+        let file = crate::retry(|| unsafe {
+            crate::sys::openat(
+                crate::CURRENT_DIRECTORY,
+                CStr::from_bytes_with_nul_unchecked(b"/dev/null\0"),
+                linux_raw_sys::general::O_RDONLY | linux_raw_sys::general::O_CLOEXEC,
+                0,
+            )
+        })
+        .unwrap();
+        unsafe {
+            assert!(!is_terminal(file.as_raw_fd()));
+            assert_eq!(
+                terminal_name(file.as_raw_fd(), &mut buf).map(|_| ()),
+                Err(Errno::ENOTTY)
+            );
+            assert!(!is_terminal(-1));
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use linux_raw_sys::general::{__kernel_timespec, UTIME_NOW, UTIME_OMIT};
+use linux_syscalls::{syscall, Errno, Sysno};
+
+use crate::{run_with_cstr, CStr, Path, RawFd, Stat, StatAtFlags, Timestamp};
+
+#[cfg(target_pointer_width = "32")]
+static HAS_UTIMENSAT_TIME64: core::sync::atomic::AtomicU8 = core::sync::atomic::AtomicU8::new(2);
+
+impl Timestamp {
+    /// Special timestamp that tells [crate::utimensat] to set the
+    /// corresponding time to the current time.
+    pub const NOW: Self = Self::new(0, UTIME_NOW);
+
+    /// Special timestamp that tells [crate::utimensat] to leave the
+    /// corresponding time unchanged. Same as passing `None`.
+    pub const OMIT: Self = Self::new(0, UTIME_OMIT);
+}
+
+#[inline]
+fn kernel_timespec(time: Option<Timestamp>) -> __kernel_timespec {
+    match time {
+        Some(time) => __kernel_timespec {
+            tv_sec: time.secs,
+            tv_nsec: time.nsecs as _,
+        },
+        None => __kernel_timespec {
+            tv_sec: 0,
+            tv_nsec: UTIME_OMIT as _,
+        },
+    }
+}
+
+#[cfg(target_pointer_width = "64")]
+#[inline]
+unsafe fn utimensat_raw(
+    dirfd: RawFd,
+    path: *const u8,
+    times: &[__kernel_timespec; 2],
+    flags: StatAtFlags,
+) -> Result<(), Errno> {
+    crate::retry(|| syscall!(Sysno::utimensat, dirfd, path, times.as_ptr(), flags.bits()))
+        .map(|_| ())
+}
+
+#[cfg(target_pointer_width = "32")]
+unsafe fn utimensat_raw(
+    dirfd: RawFd,
+    path: *const u8,
+    times: &[__kernel_timespec; 2],
+    flags: StatAtFlags,
+) -> Result<(), Errno> {
+    use core::sync::atomic::Ordering;
+
+    unsafe fn utimensat_time32(
+        dirfd: RawFd,
+        path: *const u8,
+        times: &[__kernel_timespec; 2],
+        flags: StatAtFlags,
+    ) -> Result<(), Errno> {
+        use linux_raw_sys::general::timespec;
+
+        let mut buf = [timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        }; 2];
+        for (old, new) in buf.iter_mut().zip(times.iter()) {
+            old.tv_sec = new.tv_sec.try_into().map_err(|_| Errno::EOVERFLOW)?;
+            old.tv_nsec = new.tv_nsec as _;
+        }
+        crate::retry(|| syscall!(Sysno::utimensat, dirfd, path, buf.as_ptr(), flags.bits()))
+            .map(|_| ())
+    }
+
+    match HAS_UTIMENSAT_TIME64.load(Ordering::Relaxed) {
+        0 => utimensat_time32(dirfd, path, times, flags),
+        1 => crate::retry(|| {
+            syscall!(
+                Sysno::utimensat_time64,
+                dirfd,
+                path,
+                times.as_ptr(),
+                flags.bits()
+            )
+        })
+        .map(|_| ()),
+        _ => match crate::retry(|| {
+            syscall!(
+                Sysno::utimensat_time64,
+                dirfd,
+                path,
+                times.as_ptr(),
+                flags.bits()
+            )
+        }) {
+            Err(Errno::ENOSYS) => {
+                HAS_UTIMENSAT_TIME64.store(0, Ordering::Relaxed);
+                utimensat_time32(dirfd, path, times, flags)
+            }
+            other => {
+                HAS_UTIMENSAT_TIME64.store(1, Ordering::Relaxed);
+                other.map(|_| ())
+            }
+        },
+    }
+}
+
+/// Change the access (`atime`) and modification (`mtime`) timestamps of
+/// `path` relative to `dirfd`.
+///
+/// `None` (or [Timestamp::OMIT]) leaves the timestamp unchanged,
+/// [Timestamp::NOW] sets it to the current time.
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+#[inline]
+pub unsafe fn utimensat<P: AsRef<Path>>(
+    dirfd: RawFd,
+    path: P,
+    atime: Option<Timestamp>,
+    mtime: Option<Timestamp>,
+    flags: StatAtFlags,
+) -> Result<(), Errno> {
+    run_with_cstr(path, |path| {
+        utimensat_cstr(dirfd, path, atime, mtime, flags)
+    })
+}
+
+/// Change the access (`atime`) and modification (`mtime`) timestamps of
+/// `path` relative to `dirfd`. Accept `path` as a [CStr].
+///
+/// `None` (or [Timestamp::OMIT]) leaves the timestamp unchanged,
+/// [Timestamp::NOW] sets it to the current time.
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+#[inline]
+pub unsafe fn utimensat_cstr(
+    dirfd: RawFd,
+    path: &CStr,
+    atime: Option<Timestamp>,
+    mtime: Option<Timestamp>,
+    flags: StatAtFlags,
+) -> Result<(), Errno> {
+    let times = [kernel_timespec(atime), kernel_timespec(mtime)];
+    utimensat_raw(dirfd, path.as_ptr() as *const u8, &times, flags)
+}
+
+/// Change the access (`atime`) and modification (`mtime`) timestamps of the
+/// file referred by the file descriptor `fd`.
+///
+/// `None` (or [Timestamp::OMIT]) leaves the timestamp unchanged,
+/// [Timestamp::NOW] sets it to the current time.
+///
+/// # Safety
+///
+/// This function is marked as unsafe because file descriptor (`fd`) cannot
+/// be checked.
+#[inline]
+pub unsafe fn futimens(
+    fd: RawFd,
+    atime: Option<Timestamp>,
+    mtime: Option<Timestamp>,
+) -> Result<(), Errno> {
+    if fd < 0 {
+        return Err(Errno::EBADF);
+    }
+
+    let times = [kernel_timespec(atime), kernel_timespec(mtime)];
+    utimensat_raw(fd, core::ptr::null(), &times, StatAtFlags::empty())
+}
+
+/// Copy access and modification timestamps from `stat` to `path` relative
+/// to `dirfd` through [crate::utimensat].
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+#[inline]
+pub unsafe fn utimensat_from<P: AsRef<Path>>(
+    dirfd: RawFd,
+    path: P,
+    stat: &Stat,
+    flags: StatAtFlags,
+) -> Result<(), Errno> {
+    utimensat(dirfd, path, Some(stat.atime()), Some(stat.mtime()), flags)
+}
+
+/// Copy access and modification timestamps from `stat` to `path` relative
+/// to `dirfd` through [crate::utimensat_cstr]. Accept `path` as a [CStr].
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+#[inline]
+pub unsafe fn utimensat_from_cstr(
+    dirfd: RawFd,
+    path: &CStr,
+    stat: &Stat,
+    flags: StatAtFlags,
+) -> Result<(), Errno> {
+    utimensat_cstr(dirfd, path, Some(stat.atime()), Some(stat.mtime()), flags)
+}
+
+/// Copy access and modification timestamps from `stat` to the file referred
+/// by the file descriptor `fd` through [crate::futimens].
+///
+/// # Safety
+///
+/// This function is marked as unsafe because file descriptor (`fd`) cannot
+/// be checked.
+#[inline]
+pub unsafe fn futimens_from(fd: RawFd, stat: &Stat) -> Result<(), Errno> {
+    futimens(fd, Some(stat.atime()), Some(stat.mtime()))
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::retry;
+    use crate::CURRENT_DIRECTORY;
+
+    #[test]
+    fn set_and_copy_times() {
+        linux_syscalls::init();
+
+        let dir = crate::tests::TempDir::new("utime");
+        let src = dir.join("src");
+        let dst = dir.join("dst");
+        std::fs::write(&src, b"").unwrap();
+        std::fs::write(&dst, b"").unwrap();
+
+        let atime = Timestamp::new(1_000_000_000, 123_456_789);
+        let mtime = Timestamp::new(1_500_000_000, 987_654_321);
+        retry(|| unsafe {
+            utimensat(
+                CURRENT_DIRECTORY,
+                &src,
+                Some(atime),
+                Some(mtime),
+                StatAtFlags::empty(),
+            )
+        })
+        .unwrap();
+
+        let stat = retry(|| crate::stat(&src)).unwrap();
+        assert_eq!(stat.atime(), atime);
+        assert_eq!(stat.mtime(), mtime);
+
+        retry(|| unsafe {
+            utimensat(
+                CURRENT_DIRECTORY,
+                &src,
+                None,
+                Some(Timestamp::NOW),
+                StatAtFlags::empty(),
+            )
+        })
+        .unwrap();
+        let stat = retry(|| crate::stat(&src)).unwrap();
+        assert_eq!(stat.atime(), atime);
+        assert!(stat.mtime() > mtime);
+
+        retry(|| unsafe { utimensat_from(CURRENT_DIRECTORY, &dst, &stat, StatAtFlags::empty()) })
+            .unwrap();
+        let copied = retry(|| crate::stat(&dst)).unwrap();
+        assert_eq!(copied.atime(), stat.atime());
+        assert_eq!(copied.mtime(), stat.mtime());
+    }
+}
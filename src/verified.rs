@@ -0,0 +1,312 @@
+use linux_raw_sys::general::{
+    O_CLOEXEC, O_DIRECTORY, O_NOCTTY, O_NOFOLLOW, O_PATH, O_RDONLY, RESOLVE_NO_SYMLINKS,
+};
+use linux_syscalls::Errno;
+
+use crate::{fstat, run_with_cstr, sys, CStr, FileId, Path, RawFd, Stat, CURRENT_DIRECTORY};
+
+/// File opened for reading by [crate::open_verified] after its metadata was
+/// accepted. The file descriptor is closed on drop.
+pub struct VerifiedFile {
+    fd: sys::Fd,
+    stat: Stat,
+}
+
+impl VerifiedFile {
+    /// Returns the file descriptor.
+    #[inline]
+    pub const fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+
+    /// Returns the file descriptor, which is no longer closed on drop.
+    #[inline]
+    pub fn into_raw_fd(self) -> RawFd {
+        self.fd.into_raw_fd()
+    }
+
+    /// Returns the metadata accepted by the predicate.
+    #[inline]
+    pub const fn stat(&self) -> &Stat {
+        &self.stat
+    }
+
+    /// Returns the identity of the file.
+    #[inline]
+    pub const fn file_id(&self) -> FileId {
+        FileId::from_stat(&self.stat)
+    }
+}
+
+impl core::fmt::Debug for VerifiedFile {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("VerifiedFile")
+            .field("fd", &self.as_raw_fd())
+            .field("file_id", &self.file_id())
+            .finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::os::unix::io::AsRawFd for VerifiedFile {
+    #[inline]
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::os::unix::io::IntoRawFd for VerifiedFile {
+    #[inline]
+    fn into_raw_fd(self) -> RawFd {
+        self.fd.into_raw_fd()
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<VerifiedFile> for std::fs::File {
+    #[inline]
+    fn from(file: VerifiedFile) -> Self {
+        use std::os::unix::io::FromRawFd;
+
+        unsafe { Self::from_raw_fd(file.into_raw_fd()) }
+    }
+}
+
+const NAME_MAX: usize = linux_raw_sys::general::NAME_MAX as usize;
+
+/// Open `path` with `O_PATH`, refusing symbolic links in any component with
+/// [Errno::ELOOP], with `openat2` and `RESOLVE_NO_SYMLINKS` or, before
+/// Linux 5.6, with [open_path_nofollow].
+unsafe fn open_path(dirfd: RawFd, path: &CStr) -> Result<sys::Fd, Errno> {
+    match sys::openat2(dirfd, path, O_PATH | O_CLOEXEC, RESOLVE_NO_SYMLINKS) {
+        Err(Errno::ENOSYS) => open_path_nofollow(dirfd, path),
+        other => other,
+    }
+}
+
+/// Open `path` with `O_PATH` one component at a time with `O_NOFOLLOW`,
+/// refusing symbolic links in any component like `RESOLVE_NO_SYMLINKS`.
+///
+/// A symbolic link in the last component is opened and rejected by
+/// [verify].
+unsafe fn open_path_nofollow(dirfd: RawFd, path: &CStr) -> Result<sys::Fd, Errno> {
+    let path = path.to_bytes();
+    if path.is_empty() {
+        return Err(Errno::ENOENT);
+    }
+
+    let mut cur = if path[0] == b'/' {
+        Some(sys::openat(
+            CURRENT_DIRECTORY,
+            CStr::from_bytes_with_nul_unchecked(b"/\0"),
+            O_PATH | O_DIRECTORY | O_CLOEXEC,
+            0,
+        )?)
+    } else {
+        None
+    };
+
+    let mut names = path.split(|&c| c == b'/').filter(|name| !name.is_empty());
+    let mut next = names.next();
+    while let Some(name) = next {
+        next = names.next();
+        if name.len() > NAME_MAX {
+            return Err(Errno::ENAMETOOLONG);
+        }
+        let mut cname = [0u8; NAME_MAX + 1];
+        cname[..name.len()].copy_from_slice(name);
+        let cname = CStr::from_bytes_with_nul_unchecked(&cname[..=name.len()]);
+
+        let fd = sys::openat(
+            cur.as_ref().map_or(dirfd, |fd| fd.as_raw_fd()),
+            cname,
+            O_PATH | O_NOFOLLOW | O_CLOEXEC,
+            0,
+        )?;
+        // Directories are expected before a `/`, including a trailing one.
+        if next.is_some() || path.ends_with(b"/") {
+            let stat = fstat(fd.as_raw_fd())?;
+            if stat.is_link() {
+                return Err(Errno::ELOOP);
+            }
+            if !stat.is_dir() {
+                return Err(Errno::ENOTDIR);
+            }
+        }
+        cur = Some(fd);
+    }
+
+    cur.ok_or(Errno::ENOENT)
+}
+
+unsafe fn verify<F>(path: sys::Fd, predicate: F) -> Result<VerifiedFile, Errno>
+where
+    F: FnOnce(&Stat) -> bool,
+{
+    let stat = fstat(path.as_raw_fd())?;
+    if stat.is_link() {
+        return Err(Errno::ELOOP);
+    }
+    if !predicate(&stat) {
+        return Err(Errno::EPERM);
+    }
+
+    let mut buf = [0u8; 32];
+    let proc_path = sys::proc_self_fd(path.as_raw_fd(), &mut buf);
+    let fd = sys::openat(
+        CURRENT_DIRECTORY,
+        proc_path,
+        O_RDONLY | O_NOCTTY | O_CLOEXEC,
+        0,
+    )?;
+
+    let reopened = fstat(fd.as_raw_fd())?;
+    if FileId::from_stat(&reopened) != FileId::from_stat(&stat) {
+        return Err(Errno::ESTALE);
+    }
+
+    Ok(VerifiedFile { fd, stat })
+}
+
+/// Open `path` relative to `dirfd` for reading only if its metadata is
+/// accepted by `predicate`, without races between the check and the open.
+///
+/// The file is first opened with `O_PATH` without following symbolic links
+/// in any component of `path` (`openat2` with `RESOLVE_NO_SYMLINKS`, or one
+/// component at a time with `O_NOFOLLOW` before Linux 5.6), then
+/// [crate::fstat] is passed to `predicate` and the same file is reopened
+/// through `/proc/self/fd`.
+///
+/// Returns [Errno::ELOOP] if any component of `path` is a symbolic link,
+/// on every kernel, [Errno::EPERM] if `predicate`
+/// returns false and [Errno::ESTALE] if the reopened file has a different
+/// [FileId].
+///
+/// Opening a FIFO blocks until a writer opens it, `predicate` should reject
+/// file types which are not expected.
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+#[inline]
+pub unsafe fn open_verified<P, F>(
+    dirfd: RawFd,
+    path: P,
+    predicate: F,
+) -> Result<VerifiedFile, Errno>
+where
+    P: AsRef<Path>,
+    F: FnOnce(&Stat) -> bool,
+{
+    run_with_cstr(path, |path| open_verified_cstr(dirfd, path, predicate))
+}
+
+/// Open `path` relative to `dirfd` for reading only if its metadata is
+/// accepted by `predicate`, without races between the check and the open.
+/// Accept `path` as a [CStr].
+///
+/// See [crate::open_verified].
+///
+/// # Safety
+///
+/// This function is marked as unsafe because directory file descriptor
+/// (`dirfd`) cannot be checked.
+#[inline]
+pub unsafe fn open_verified_cstr<F>(
+    dirfd: RawFd,
+    path: &CStr,
+    predicate: F,
+) -> Result<VerifiedFile, Errno>
+where
+    F: FnOnce(&Stat) -> bool,
+{
+    verify(open_path(dirfd, path)?, predicate)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+    use crate::retry;
+
+    #[test]
+    fn open_and_verify() {
+        linux_syscalls::init();
+
+        let dir = crate::tests::TempDir::new("verified");
+        let file = dir.join("file");
+        let link = dir.join("link");
+        std::fs::write(&file, b"content").unwrap();
+        std::os::unix::fs::symlink(&file, &link).unwrap();
+
+        let verified =
+            retry(|| unsafe { open_verified(CURRENT_DIRECTORY, &file, |stat| stat.is_regular()) })
+                .unwrap();
+        assert_eq!(
+            verified.file_id(),
+            FileId::from_stat(&crate::stat(&file).unwrap())
+        );
+        let mut content = String::new();
+        std::fs::File::from(verified)
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "content");
+
+        assert_eq!(
+            retry(|| unsafe { open_verified(CURRENT_DIRECTORY, &file, |stat| stat.is_dir()) })
+                .map(|_| ()),
+            Err(Errno::EPERM)
+        );
+        assert_eq!(
+            retry(|| unsafe { open_verified(CURRENT_DIRECTORY, &link, |_| true) }).map(|_| ()),
+            Err(Errno::ELOOP)
+        );
+
+        let sub = dir.join("sub");
+        let sub_link = dir.join("sub_link");
+        std::fs::create_dir(&sub).unwrap();
+        std::fs::write(sub.join("file"), b"content").unwrap();
+        std::os::unix::fs::symlink(&sub, &sub_link).unwrap();
+
+        for path in [&link, &sub_link.join("file"), &sub_link.join(".")] {
+            assert_eq!(
+                retry(|| unsafe { open_verified(CURRENT_DIRECTORY, path, |_| true) }).map(|_| ()),
+                Err(Errno::ELOOP),
+                "{:?}",
+                path
+            );
+            assert_eq!(
+                retry(|| unsafe {
+                    run_with_cstr(path, |path| {
+                        verify(open_path_nofollow(CURRENT_DIRECTORY, path)?, |_| true)
+                    })
+                })
+                .map(|_| ()),
+                Err(Errno::ELOOP),
+                "{:?}",
+                path
+            );
+        }
+
+        for path in [sub.join("file"), sub.join("../sub/./file")] {
+            let id = retry(|| unsafe {
+                run_with_cstr(&path, |path| {
+                    verify(open_path_nofollow(CURRENT_DIRECTORY, path)?, |_| true)
+                })
+            })
+            .map(|file| file.file_id());
+            assert_eq!(id, Ok(FileId::from_stat(&crate::stat(&path).unwrap())));
+        }
+        assert_eq!(
+            retry(|| unsafe {
+                run_with_cstr(file.join(""), |path| {
+                    open_path_nofollow(CURRENT_DIRECTORY, path).map(|_| ())
+                })
+            }),
+            Err(Errno::ENOTDIR)
+        );
+    }
+}